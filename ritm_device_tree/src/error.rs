@@ -10,6 +10,9 @@
 
 use core::fmt;
 
+/// A specialized [`core::result::Result`] type for fallible FDT operations.
+pub type Result<T> = core::result::Result<T, FdtError>;
+
 /// An error that can occur when parsing a device tree.
 #[derive(Debug)]
 #[non_exhaustive]
@@ -26,23 +29,51 @@ impl FdtError {
 }
 
 /// The kind of an error that can occur when parsing a device tree.
+///
+/// These kinds mirror the `FDT_ERR_*` taxonomy used by the canonical libfdt
+/// C library, so that callers familiar with libfdt (e.g. a bootloader
+/// deciding whether to reject a blob outright) can map failures onto
+/// behavior they already know.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum FdtErrorKind {
     /// The magic number of the device tree is invalid.
-    InvalidMagic,
+    BadMagic,
     /// The Device Tree version is not supported by this library.
-    UnsupportedVersion(u32),
+    BadVersion(u32),
     /// The length of the device tree is invalid.
     InvalidLength,
     /// The header failed validation.
     InvalidHeader(&'static str),
     /// An invalid token was encountered.
     BadToken(u32),
-    /// A read from data at invalid offset was attempted.
-    InvalidOffset,
-    /// An invalid string was encountered.
+    /// A read from data at an offset outside the relevant block (e.g. past
+    /// the end of the strings block) was attempted.
+    BadOffset,
+    /// An invalid string was encountered (e.g. not valid UTF-8).
     InvalidString,
+    /// The blob ended before a structurally required field, token, or value
+    /// could be fully read.
+    Truncated,
+    /// A path argument was malformed (e.g. did not start with `/`).
+    BadPath,
+    /// A `phandle` property held a reserved value (`0` or `0xffffffff`).
+    BadPhandle,
+    /// A destination buffer did not have enough room to hold the output.
+    NoSpace,
+    /// The requested item does not exist.
+    ///
+    /// The read-only traversal API (e.g. [`Fdt::find_node`](crate::fdt::Fdt::find_node),
+    /// [`FdtNode::property`](crate::fdt::FdtNode::property)) represents
+    /// absence as `None` rather than this variant, since a missing node or
+    /// property is an expected outcome there, not a parse failure. This kind
+    /// is reserved for APIs where only a `Result` is returned.
+    NotFound,
+    /// A bus address could not be translated to its parent's address space,
+    /// either because the bus has no `ranges` property at all (it is not
+    /// memory-mapped), or because the address doesn't fall within any of the
+    /// windows a non-empty `ranges` property describes.
+    NotMemoryMapped,
 }
 
 impl fmt::Display for FdtError {
@@ -54,8 +85,8 @@ impl fmt::Display for FdtError {
 impl fmt::Display for FdtErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            FdtErrorKind::InvalidMagic => write!(f, "invalid FDT magic number"),
-            FdtErrorKind::UnsupportedVersion(version) => {
+            FdtErrorKind::BadMagic => write!(f, "invalid FDT magic number"),
+            FdtErrorKind::BadVersion(version) => {
                 write!(f, "the FDT version {version} is not supported")
             }
             FdtErrorKind::InvalidLength => write!(f, "invalid FDT length"),
@@ -63,8 +94,16 @@ impl fmt::Display for FdtErrorKind {
                 write!(f, "FDT header has failed validation: {msg}")
             }
             FdtErrorKind::BadToken(token) => write!(f, "bad FDT token: 0x{token:x}"),
-            FdtErrorKind::InvalidOffset => write!(f, "invalid offset in FDT"),
+            FdtErrorKind::BadOffset => write!(f, "offset outside the relevant FDT block"),
             FdtErrorKind::InvalidString => write!(f, "invalid string in FDT"),
+            FdtErrorKind::Truncated => write!(f, "FDT blob is truncated"),
+            FdtErrorKind::BadPath => write!(f, "malformed device tree path"),
+            FdtErrorKind::BadPhandle => write!(f, "phandle value is reserved or invalid"),
+            FdtErrorKind::NoSpace => write!(f, "not enough space in destination buffer"),
+            FdtErrorKind::NotFound => write!(f, "requested item not found"),
+            FdtErrorKind::NotMemoryMapped => {
+                write!(f, "address is not memory-mapped through this bus")
+            }
         }
     }
 }
@@ -0,0 +1,390 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A sequential, allocation-free builder for flattened device tree blobs.
+
+use zerocopy::IntoBytes;
+
+use super::{FDT_BEGIN_NODE, FDT_END, FDT_END_NODE, FDT_MAGIC, FDT_PROP, Fdt, FdtHeader};
+use crate::error::{FdtError, FdtErrorKind};
+
+const LAST_VERSION: u32 = 17;
+const LAST_COMP_VERSION: u32 = 16;
+
+/// A sequential builder for flattened device tree blobs.
+///
+/// Unlike [`DeviceTree`](crate::model::DeviceTree), which requires the
+/// `alloc` feature, `FdtBuilder` writes directly into a caller-provided byte
+/// buffer and keeps its deduplicated property-name strings in a
+/// fixed-capacity internal array, making it usable in `no_std`, no-`alloc`
+/// environments such as bootloaders.
+///
+/// Nodes and properties must be written in the same depth-first order as the
+/// resulting tree: call [`begin_node`](Self::begin_node), then any
+/// properties and child nodes of that node, then [`end_node`](Self::end_node).
+/// Any memory reservations must be added with
+/// [`add_memory_reservation`](Self::add_memory_reservation) before the first
+/// call to `begin_node`.
+///
+/// `MAX_STRINGS` bounds the total size in bytes of the property-name strings
+/// block.
+///
+/// # Examples
+///
+/// ```
+/// # use ritm_device_tree::fdt::{Fdt, FdtBuilder};
+/// let mut buf = [0u8; 256];
+/// let mut builder = FdtBuilder::<64>::new(&mut buf).unwrap();
+/// builder.begin_node("").unwrap();
+/// builder.property_str("compatible", "my,board").unwrap();
+/// builder.begin_node("child").unwrap();
+/// builder.property_u32("my-prop", 42).unwrap();
+/// builder.end_node().unwrap();
+/// builder.end_node().unwrap();
+/// let len = builder.finish().unwrap();
+///
+/// let fdt = Fdt::new(&buf[..len]).unwrap();
+/// let child = fdt.find_node("/child").unwrap().unwrap();
+/// assert_eq!(child.property("my-prop").unwrap().unwrap().as_u32().unwrap(), 42);
+/// ```
+pub struct FdtBuilder<'a, const MAX_STRINGS: usize> {
+    buf: &'a mut [u8],
+    pos: usize,
+    off_dt_struct: Option<usize>,
+    strings: [u8; MAX_STRINGS],
+    strings_len: usize,
+    depth: u32,
+}
+
+impl<'a, const MAX_STRINGS: usize> FdtBuilder<'a, MAX_STRINGS> {
+    /// Creates a new builder that writes into `buf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` is too small to
+    /// hold an FDT header.
+    pub fn new(buf: &'a mut [u8]) -> Result<Self, FdtError> {
+        if buf.len() < size_of::<FdtHeader>() {
+            return Err(FdtError::new(FdtErrorKind::NoSpace, 0));
+        }
+        Ok(Self {
+            buf,
+            pos: size_of::<FdtHeader>(),
+            off_dt_struct: None,
+            strings: [0; MAX_STRINGS],
+            strings_len: 0,
+            depth: 0,
+        })
+    }
+
+    /// Adds a memory reservation.
+    ///
+    /// Must be called before the first call to [`begin_node`](Self::begin_node).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` is too small, or
+    /// an [`FdtErrorKind::InvalidHeader`] if a node has already been started.
+    pub fn add_memory_reservation(&mut self, address: u64, size: u64) -> Result<(), FdtError> {
+        if self.off_dt_struct.is_some() {
+            return Err(FdtError::new(
+                FdtErrorKind::InvalidHeader("memory reservations must precede all nodes"),
+                self.pos,
+            ));
+        }
+        self.write_bytes(&address.to_be_bytes())?;
+        self.write_bytes(&size.to_be_bytes())
+    }
+
+    /// Starts a new node named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` is too small.
+    pub fn begin_node(&mut self, name: &str) -> Result<(), FdtError> {
+        self.start_struct_block_if_needed()?;
+        self.write_u32(FDT_BEGIN_NODE)?;
+        self.write_bytes(name.as_bytes())?;
+        self.write_bytes(&[0])?;
+        self.align()?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Ends the most recently started node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidHeader`] if there is no open node to
+    /// end, or an [`FdtErrorKind::NoSpace`] if `buf` is too small.
+    pub fn end_node(&mut self) -> Result<(), FdtError> {
+        let Some(depth) = self.depth.checked_sub(1) else {
+            return Err(FdtError::new(
+                FdtErrorKind::InvalidHeader("end_node called with no open node"),
+                self.pos,
+            ));
+        };
+        self.depth = depth;
+        self.write_u32(FDT_END_NODE)
+    }
+
+    /// Adds a property with a raw byte value to the current node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` or the strings
+    /// array is too small.
+    pub fn property(&mut self, name: &str, value: &[u8]) -> Result<(), FdtError> {
+        let name_offset = self.intern_string(name)?;
+        self.write_u32(FDT_PROP)?;
+        self.write_u32(
+            u32::try_from(value.len())
+                .map_err(|_e| FdtError::new(FdtErrorKind::InvalidLength, self.pos))?,
+        )?;
+        self.write_u32(name_offset)?;
+        self.write_bytes(value)?;
+        self.align()
+    }
+
+    /// Adds a `u32`-valued property to the current node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` or the strings
+    /// array is too small.
+    pub fn property_u32(&mut self, name: &str, value: u32) -> Result<(), FdtError> {
+        self.property(name, &value.to_be_bytes())
+    }
+
+    /// Adds a `u64`-valued property to the current node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` or the strings
+    /// array is too small.
+    pub fn property_u64(&mut self, name: &str, value: u64) -> Result<(), FdtError> {
+        self.property(name, &value.to_be_bytes())
+    }
+
+    /// Adds a string-valued property to the current node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` or the strings
+    /// array is too small.
+    pub fn property_str(&mut self, name: &str, value: &str) -> Result<(), FdtError> {
+        self.property_str_list(name, core::iter::once(value))
+    }
+
+    /// Adds a string-list-valued property to the current node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` or the strings
+    /// array is too small.
+    pub fn property_str_list<'s>(
+        &mut self,
+        name: &str,
+        values: impl IntoIterator<Item = &'s str>,
+    ) -> Result<(), FdtError> {
+        let name_offset = self.intern_string(name)?;
+        self.write_u32(FDT_PROP)?;
+        let len_offset = self.pos;
+        self.write_u32(0)?; // Patched below once the value length is known.
+        self.write_u32(name_offset)?;
+        let value_start = self.pos;
+        for value in values {
+            self.write_bytes(value.as_bytes())?;
+            self.write_bytes(&[0])?;
+        }
+        let value_len = u32::try_from(self.pos - value_start)
+            .map_err(|_e| FdtError::new(FdtErrorKind::InvalidLength, self.pos))?;
+        self.buf[len_offset..len_offset + 4].copy_from_slice(&value_len.to_be_bytes());
+        self.align()
+    }
+
+    /// Adds a cell-array-valued property (e.g. `interrupts`, `clocks`, or a
+    /// multi-entry `reg`) to the current node, writing each `u32` in
+    /// `cells` as a big-endian cell.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::NoSpace`] if `buf` or the strings
+    /// array is too small.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::{Fdt, FdtBuilder};
+    /// let mut buf = [0u8; 256];
+    /// let mut builder = FdtBuilder::<64>::new(&mut buf).unwrap();
+    /// builder.begin_node("").unwrap();
+    /// builder.property_cells("interrupts", [0, 1, 4]).unwrap();
+    /// builder.end_node().unwrap();
+    /// let len = builder.finish().unwrap();
+    ///
+    /// let fdt = Fdt::new(&buf[..len]).unwrap();
+    /// let root = fdt.root().unwrap();
+    /// let prop = root.property("interrupts").unwrap().unwrap();
+    /// assert_eq!(prop.as_cells().unwrap().collect::<Vec<_>>(), [0, 1, 4]);
+    /// ```
+    pub fn property_cells(
+        &mut self,
+        name: &str,
+        cells: impl IntoIterator<Item = u32>,
+    ) -> Result<(), FdtError> {
+        let name_offset = self.intern_string(name)?;
+        self.write_u32(FDT_PROP)?;
+        let len_offset = self.pos;
+        self.write_u32(0)?; // Patched below once the value length is known.
+        self.write_u32(name_offset)?;
+        let value_start = self.pos;
+        for cell in cells {
+            self.write_u32(cell)?;
+        }
+        let value_len = u32::try_from(self.pos - value_start)
+            .map_err(|_e| FdtError::new(FdtErrorKind::InvalidLength, self.pos))?;
+        self.buf[len_offset..len_offset + 4].copy_from_slice(&value_len.to_be_bytes());
+        self.align()
+    }
+
+    /// Finishes building the device tree, writing the header and strings
+    /// block, and returns the total length of the blob written to the
+    /// buffer passed to [`FdtBuilder::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidHeader`] if any node started with
+    /// [`begin_node`](Self::begin_node) was not matched with a call to
+    /// [`end_node`](Self::end_node), or an [`FdtErrorKind::NoSpace`] if
+    /// `buf` is too small.
+    pub fn finish(mut self) -> Result<usize, FdtError> {
+        if self.depth != 0 {
+            return Err(FdtError::new(
+                FdtErrorKind::InvalidHeader("finish called with unclosed nodes"),
+                self.pos,
+            ));
+        }
+        self.start_struct_block_if_needed()?;
+        self.write_u32(FDT_END)?;
+
+        let off_dt_struct = self.off_dt_struct.expect("set by start_struct_block_if_needed");
+        let size_dt_struct = self.pos - off_dt_struct;
+        let off_dt_strings = self.pos;
+        self.write_bytes(&self.strings_slice())?;
+        let totalsize = self.pos;
+
+        let header = FdtHeader {
+            magic: FDT_MAGIC.into(),
+            totalsize: u32::try_from(totalsize)
+                .expect("totalsize exceeds u32")
+                .into(),
+            off_dt_struct: u32::try_from(off_dt_struct)
+                .expect("off_dt_struct exceeds u32")
+                .into(),
+            off_dt_strings: u32::try_from(off_dt_strings)
+                .expect("off_dt_strings exceeds u32")
+                .into(),
+            off_mem_rsvmap: u32::try_from(size_of::<FdtHeader>())
+                .expect("header size exceeds u32")
+                .into(),
+            version: LAST_VERSION.into(),
+            last_comp_version: LAST_COMP_VERSION.into(),
+            boot_cpuid_phys: 0u32.into(),
+            size_dt_strings: u32::try_from(self.strings_len)
+                .expect("size_dt_strings exceeds u32")
+                .into(),
+            size_dt_struct: u32::try_from(size_dt_struct)
+                .expect("size_dt_struct exceeds u32")
+                .into(),
+        };
+        self.buf[..size_of::<FdtHeader>()].copy_from_slice(header.as_bytes());
+
+        Ok(totalsize)
+    }
+
+    /// Writes the terminating all-zero memory reservation entry and records
+    /// the start of the struct block, if this is the first call to
+    /// `begin_node` or `finish`.
+    fn start_struct_block_if_needed(&mut self) -> Result<(), FdtError> {
+        if self.off_dt_struct.is_none() {
+            self.write_bytes(&0u64.to_be_bytes())?;
+            self.write_bytes(&0u64.to_be_bytes())?;
+            self.off_dt_struct = Some(self.pos);
+        }
+        Ok(())
+    }
+
+    /// Interns `name` into the strings array, returning its offset. Returns
+    /// the existing offset if `name` was already interned.
+    fn intern_string(&mut self, name: &str) -> Result<u32, FdtError> {
+        let existing = self.strings_slice();
+        if let Some(offset) = find_nul_terminated(existing, name.as_bytes()) {
+            return Ok(u32::try_from(offset).expect("strings array is within u32 range"));
+        }
+
+        let offset = self.strings_len;
+        let len = name.len() + 1;
+        if offset + len > self.strings.len() {
+            return Err(FdtError::new(FdtErrorKind::NoSpace, self.pos));
+        }
+        self.strings[offset..offset + name.len()].copy_from_slice(name.as_bytes());
+        self.strings[offset + name.len()] = 0;
+        self.strings_len += len;
+
+        Ok(u32::try_from(offset).expect("strings array is within u32 range"))
+    }
+
+    fn strings_slice(&self) -> &[u8] {
+        &self.strings[..self.strings_len]
+    }
+
+    fn write_u32(&mut self, value: u32) -> Result<(), FdtError> {
+        self.write_bytes(&value.to_be_bytes())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), FdtError> {
+        let end = self
+            .pos
+            .checked_add(bytes.len())
+            .ok_or(FdtError::new(FdtErrorKind::NoSpace, self.pos))?;
+        let dest = self
+            .buf
+            .get_mut(self.pos..end)
+            .ok_or(FdtError::new(FdtErrorKind::NoSpace, self.pos))?;
+        dest.copy_from_slice(bytes);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn align(&mut self) -> Result<(), FdtError> {
+        let aligned = Fdt::align_tag_offset(self.pos);
+        if aligned > self.pos {
+            let padding = aligned - self.pos;
+            for _ in 0..padding {
+                self.write_bytes(&[0])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the offset of `needle` within `haystack` if `haystack` contains
+/// `needle` immediately followed by a NUL byte (i.e. as a standalone,
+/// null-terminated entry).
+fn find_nul_terminated(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let mut offset = 0;
+    while offset < haystack.len() {
+        let len = haystack[offset..].iter().position(|&b| b == 0)?;
+        if &haystack[offset..offset + len] == needle {
+            return Some(offset);
+        }
+        offset += len + 1;
+    }
+    None
+}
@@ -17,14 +17,21 @@
 
 use crate::error::{FdtError, FdtErrorKind};
 use crate::memreserve::MemoryReservation;
+mod builder;
 mod node;
 mod property;
+mod yaml;
 use core::ffi::CStr;
 use core::mem::offset_of;
 use core::{fmt, ptr};
 
+pub use builder::FdtBuilder;
 pub use node::FdtNode;
-pub use property::FdtProperty;
+pub use property::{
+    AddressRange, CellIterator, FdtProperty, FdtStringListIterator, PropertyValue, RangesIterator, Reg,
+    RegIterator,
+};
+pub use yaml::Yaml;
 use zerocopy::byteorder::big_endian;
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
@@ -141,14 +148,14 @@ impl<'a> Fdt<'a> {
     ///
     /// # Errors
     ///
-    /// Returns an [`FdtErrorKind::InvalidLength`] if `data` is too short to
+    /// Returns an [`FdtErrorKind::Truncated`] if `data` is too short to
     /// contain a valid FDT header or if the `totalsize` field in the header
     /// does not match the length of `data`.
     ///
-    /// Returns an [`FdtErrorKind::InvalidMagic`] if the `magic` field in the
+    /// Returns an [`FdtErrorKind::BadMagic`] if the `magic` field in the
     /// header is not `0xd00dfeed`.
     ///
-    /// Returns an [`FdtErrorKind::UnsupportedVersion`] if the `version` field
+    /// Returns an [`FdtErrorKind::BadVersion`] if the `version` field
     /// in the header is not supported by this library.
     ///
     /// Returns an [`FdtErrorKind::InvalidHeader`] if the header fails to pass
@@ -163,7 +170,7 @@ impl<'a> Fdt<'a> {
     /// ```
     pub fn new(data: &'a [u8]) -> Result<Self, FdtError> {
         if data.len() < size_of::<FdtHeader>() {
-            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+            return Err(FdtError::new(FdtErrorKind::Truncated, 0));
         }
 
         let fdt = Fdt { data };
@@ -171,20 +178,20 @@ impl<'a> Fdt<'a> {
 
         if header.magic() != FDT_MAGIC {
             return Err(FdtError::new(
-                FdtErrorKind::InvalidMagic,
+                FdtErrorKind::BadMagic,
                 offset_of!(FdtHeader, magic),
             ));
         }
         if !(header.last_comp_version()..=header.version()).contains(&FDT_VERSION) {
             return Err(FdtError::new(
-                FdtErrorKind::UnsupportedVersion(header.version()),
+                FdtErrorKind::BadVersion(header.version()),
                 offset_of!(FdtHeader, version),
             ));
         }
 
         if header.totalsize() as usize != data.len() {
             return Err(FdtError::new(
-                FdtErrorKind::InvalidLength,
+                FdtErrorKind::Truncated,
                 offset_of!(FdtHeader, totalsize),
             ));
         }
@@ -239,6 +246,18 @@ impl<'a> Fdt<'a> {
         let off_mem_rsvmap = header.off_mem_rsvmap() as usize;
         let off_dt_struct = header.off_dt_struct() as usize;
         let off_dt_strings = header.off_dt_strings() as usize;
+        if !off_mem_rsvmap.is_multiple_of(8) {
+            return Err(FdtError::new(
+                FdtErrorKind::InvalidHeader("off_mem_rsvmap is not 8-byte aligned"),
+                offset_of!(FdtHeader, off_mem_rsvmap),
+            ));
+        }
+        if !off_dt_struct.is_multiple_of(FDT_TAGSIZE) {
+            return Err(FdtError::new(
+                FdtErrorKind::InvalidHeader("off_dt_struct is not 4-byte aligned"),
+                offset_of!(FdtHeader, off_dt_struct),
+            ));
+        }
         if off_mem_rsvmap > off_dt_struct {
             return Err(FdtError::new(
                 FdtErrorKind::InvalidHeader("dt_struct not after memrsvmap"),
@@ -282,6 +301,102 @@ impl<'a> Fdt<'a> {
         Ok(())
     }
 
+    /// Walks the entire structure block, verifying the integrity of the
+    /// token stream itself rather than just the header offsets and sizes
+    /// [`Fdt::new`] already checks.
+    ///
+    /// A blob can pass [`Fdt::new`] yet still be malformed enough to make
+    /// traversal methods like [`Fdt::root`] misbehave, e.g. a crafted or
+    /// corrupted struct block with an unbalanced `FDT_BEGIN_NODE`/
+    /// `FDT_END_NODE` pair. This walks the whole struct block up front and
+    /// checks: every tag is one of the five known tokens; every
+    /// `FDT_BEGIN_NODE` is balanced by an `FDT_END_NODE`; node names and
+    /// property values never read past the end of the struct block; every
+    /// `FDT_PROP`'s string-block offset is in range and NUL-terminated; and
+    /// the block ends with exactly one `FDT_END` tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::BadToken`] if an unrecognized tag is
+    /// encountered, or an [`FdtErrorKind::Truncated`] if the struct block
+    /// ends mid-node, with unbalanced nodes, or without exactly one
+    /// trailing `FDT_END`. Property name offsets are validated by
+    /// [`Fdt::string`], via the same [`FdtErrorKind::BadOffset`]/
+    /// [`FdtErrorKind::InvalidString`] errors it returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// fdt.validate_structure().unwrap();
+    /// ```
+    pub fn validate_structure(&self) -> Result<(), FdtError> {
+        let header = self.header();
+        let start = header.off_dt_struct() as usize;
+        let end = start.saturating_add(header.size_dt_struct() as usize);
+
+        let mut offset = start;
+        let mut depth: u32 = 0;
+
+        loop {
+            if offset + FDT_TAGSIZE > end {
+                return Err(FdtError::new(FdtErrorKind::Truncated, offset));
+            }
+            let token = self.read_token(offset)?;
+            offset += FDT_TAGSIZE;
+
+            match token {
+                FdtToken::BeginNode => {
+                    offset = self.find_string_end(offset)?;
+                    if offset > end {
+                        return Err(FdtError::new(FdtErrorKind::Truncated, offset));
+                    }
+                    offset = Self::align_tag_offset(offset);
+                    depth += 1;
+                }
+                FdtToken::EndNode => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or_else(|| FdtError::new(FdtErrorKind::Truncated, offset))?;
+                }
+                FdtToken::Prop => {
+                    if offset + 2 * FDT_TAGSIZE > end {
+                        return Err(FdtError::new(FdtErrorKind::Truncated, offset));
+                    }
+                    let len = big_endian::U32::ref_from_prefix(&self.data[offset..])
+                        .map(|(val, _)| val.get())
+                        .map_err(|_e| FdtError::new(FdtErrorKind::Truncated, offset))?
+                        as usize;
+                    let nameoff = big_endian::U32::ref_from_prefix(&self.data[offset + FDT_TAGSIZE..])
+                        .map(|(val, _)| val.get())
+                        .map_err(|_e| FdtError::new(FdtErrorKind::Truncated, offset))?
+                        as usize;
+                    offset += 2 * FDT_TAGSIZE;
+                    if offset.saturating_add(len) > end {
+                        return Err(FdtError::new(FdtErrorKind::Truncated, offset));
+                    }
+                    self.string(nameoff)?;
+                    offset = Self::align_tag_offset(offset + len);
+                }
+                FdtToken::Nop => {}
+                FdtToken::End => {
+                    if depth != 0 {
+                        return Err(FdtError::new(FdtErrorKind::Truncated, offset));
+                    }
+                    break;
+                }
+            }
+        }
+
+        if offset != end {
+            return Err(FdtError::new(FdtErrorKind::Truncated, offset));
+        }
+
+        Ok(())
+    }
+
     /// Returns the header of the device tree.
     pub(crate) fn header(&self) -> &FdtHeader {
         let (header, _remaining_bytes) = FdtHeader::ref_from_prefix(self.data)
@@ -328,6 +443,89 @@ impl<'a> Fdt<'a> {
         })
     }
 
+    /// Finds the node whose `phandle` (or legacy `linux,phandle`) property
+    /// equals `phandle`, searching depth-first from the root.
+    ///
+    /// This lets consumers follow references like `interrupt-parent`,
+    /// `clocks`, or `gpios` that encode their target node by phandle. See
+    /// [`Fdt::find_compatible`]/[`Fdt::all_compatible`] for the analogous
+    /// whole-tree search keyed by `compatible` string instead.
+    ///
+    /// # Performance
+    ///
+    /// This method traverses the entire device tree until a match is found
+    /// and its performance is linear in the number of nodes visited. If you
+    /// need to call this often, consider using
+    /// [`DeviceTree::from_fdt`](crate::model::DeviceTree::from_fdt) first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_phandle.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.node_by_phandle(1).unwrap().unwrap();
+    /// ```
+    pub fn node_by_phandle(&self, phandle: u32) -> Option<Result<FdtNode<'_>, FdtError>> {
+        let root = match self.root() {
+            Ok(root) => root,
+            Err(e) => return Some(Err(e)),
+        };
+        Self::node_by_phandle_recursive(root, phandle)
+    }
+
+    fn node_by_phandle_recursive<'n>(
+        node: FdtNode<'n>,
+        phandle: u32,
+    ) -> Option<Result<FdtNode<'n>, FdtError>> {
+        match node.phandle() {
+            Ok(Some(value)) if value == phandle => return Some(Ok(node)),
+            Ok(_) => {}
+            Err(e) => return Some(Err(e)),
+        }
+        for child in node.children() {
+            let child = match child {
+                Ok(child) => child,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Some(result) = Self::node_by_phandle_recursive(child, phandle) {
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    /// Returns an iterator over the usable RAM ranges described by the
+    /// `/memory` node's `reg` property.
+    ///
+    /// The `reg` property is decoded using the root node's
+    /// `#address-cells`/`#size-cells`, as is standard for the `/memory`
+    /// node.
+    ///
+    /// Returns `None` if there is no `/memory` node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_memory.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let mut regions = fdt.memory_regions().unwrap().unwrap();
+    /// let first = regions.next().unwrap();
+    /// assert_eq!(first.address, 0x4000_0000);
+    /// ```
+    pub fn memory_regions(&self) -> Option<Result<RegIterator<'_>, FdtError>> {
+        let memory = match self.find_node("/memory")? {
+            Ok(memory) => memory,
+            Err(e) => return Some(Err(e)),
+        };
+        let root = match self.root() {
+            Ok(root) => root,
+            Err(e) => return Some(Err(e)),
+        };
+        memory.reg(&root).transpose()
+    }
+
     /// Returns the root node of the device tree.
     ///
     /// # Errors
@@ -368,6 +566,11 @@ impl<'a> Fdt<'a> {
     /// first. [`DeviceTree`](crate::model::DeviceTree) stores the nodes in a
     /// hash map for constant-time lookup.
     ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::BadPath`] if `path` does not start with
+    /// `/`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -380,7 +583,7 @@ impl<'a> Fdt<'a> {
     #[must_use]
     pub fn find_node(&self, path: &str) -> Option<Result<FdtNode<'_>, FdtError>> {
         if !path.starts_with('/') {
-            return None;
+            return Some(Err(FdtError::new(FdtErrorKind::BadPath, 0)));
         }
         let mut current_node = match self.root() {
             Ok(node) => node,
@@ -403,10 +606,192 @@ impl<'a> Fdt<'a> {
         Some(Ok(current_node))
     }
 
+    /// Finds the first node in the tree whose `compatible` property contains
+    /// `name`, searching depth-first from the root.
+    ///
+    /// # Performance
+    ///
+    /// This method traverses the entire device tree until a match is found
+    /// and its performance is linear in the number of nodes visited. If you
+    /// need to call this often, consider using
+    /// [`DeviceTree::from_fdt`](crate::model::DeviceTree::from_fdt) first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_compatible.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_compatible("vendor,uart").unwrap().unwrap();
+    /// assert_eq!(node.name().unwrap(), "uart");
+    /// ```
+    pub fn find_compatible(&self, name: &str) -> Option<Result<FdtNode<'_>, FdtError>> {
+        self.all_compatible(core::slice::from_ref(&name)).next()
+    }
+
+    /// Returns an iterator over every node in the tree whose `compatible`
+    /// property contains any of `compatibles`, searching depth-first from
+    /// the root.
+    ///
+    /// # Performance
+    ///
+    /// This traverses the entire device tree, via
+    /// [`FdtNode::descendants`]. If you need to call this often, consider
+    /// using [`DeviceTree::from_fdt`](crate::model::DeviceTree::from_fdt)
+    /// first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_compatible.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let mut matches = fdt.all_compatible(&["vendor,uart", "vendor,other"]);
+    /// assert_eq!(matches.next().unwrap().unwrap().name().unwrap(), "uart");
+    /// assert!(matches.next().is_none());
+    /// ```
+    pub fn all_compatible<'a, 'c>(
+        &'a self,
+        compatibles: &'c [&'c str],
+    ) -> impl Iterator<Item = Result<FdtNode<'a>, FdtError>> + use<'a, 'c> {
+        let root = self.root();
+        let descendants = root.as_ref().ok().map(FdtNode::descendants);
+        core::iter::once(root)
+            .chain(descendants.into_iter().flatten())
+            .filter_map(move |node| match node {
+                Ok(node) => match is_any_compatible(&node, compatibles) {
+                    Ok(true) => Some(Ok(node)),
+                    Ok(false) => None,
+                    Err(e) => Some(Err(e)),
+                },
+                Err(e) => Some(Err(e)),
+            })
+    }
+
+    /// Resolves an alias name (a property of the `/aliases` node) to the
+    /// node it names.
+    ///
+    /// Returns `None` if there is no `/aliases` node, or if it has no
+    /// property named `name`.
+    ///
+    /// # Performance
+    ///
+    /// This traverses the device tree and its performance is linear in the
+    /// number of path segments the alias resolves to. If you need to call
+    /// this often, consider using
+    /// [`DeviceTree::from_fdt`](crate::model::DeviceTree::from_fdt) first,
+    /// and [`DeviceTree::resolve_alias`](crate::model::DeviceTree::resolve_alias).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_aliases.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.alias("serial0").unwrap().unwrap();
+    /// assert_eq!(node.name().unwrap(), "uart");
+    /// ```
+    pub fn alias(&self, name: &str) -> Option<Result<FdtNode<'_>, FdtError>> {
+        let aliases = match self.find_node("/aliases")? {
+            Ok(aliases) => aliases,
+            Err(e) => return Some(Err(e)),
+        };
+        let path = match aliases.property(name) {
+            Ok(Some(prop)) => match prop.as_str() {
+                Ok(path) => path,
+                Err(e) => return Some(Err(e)),
+            },
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+        self.find_node(path)
+    }
+
+    /// Returns a [`Chosen`] view of the `/chosen` node.
+    ///
+    /// Returns `None` if there is no `/chosen` node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_chosen.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let chosen = fdt.chosen().unwrap().unwrap();
+    /// assert_eq!(chosen.bootargs().unwrap(), Some("console=ttyAMA0"));
+    /// ```
+    pub fn chosen(&self) -> Option<Result<Chosen<'_>, FdtError>> {
+        match self.find_node("/chosen")? {
+            Ok(node) => Some(Ok(Chosen { node })),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Translates a bus address into a CPU physical address.
+    ///
+    /// `path` identifies the bus node whose child address space `address` is
+    /// expressed in (e.g. the parent of the node whose `reg` property
+    /// `address` came from). This walks up from `path` to the root, applying
+    /// each ancestor's `ranges` property in turn (see
+    /// [`FdtNode::ranges`]/[`FdtNode::translate_through_ranges`], which
+    /// decode a `ranges` property using the governing `#address-cells`/
+    /// `#size-cells`, the same way [`FdtNode::reg`] does for `reg`), so that
+    /// MMIO addresses can be resolved correctly across bus bridges.
+    ///
+    /// Returns `None` if `path` does not identify a node in the tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::BadPath`] if `path` does not start with
+    /// `/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_ranges.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let phys = fdt.translate_address("/soc", 0x1000).unwrap().unwrap();
+    /// ```
+    #[must_use]
+    pub fn translate_address(&self, path: &str, address: u64) -> Option<crate::Result<u64>> {
+        if !path.starts_with('/') {
+            return Some(Err(FdtError::new(FdtErrorKind::BadPath, 0)));
+        }
+        let root = match self.root() {
+            Ok(root) => root,
+            Err(e) => return Some(Err(e)),
+        };
+        if path == "/" {
+            return Some(Ok(address));
+        }
+        Self::translate_address_recursive(root, path.split('/').filter(|s| !s.is_empty()), address)
+    }
+
+    fn translate_address_recursive<'p>(
+        node: FdtNode<'a>,
+        mut components: impl Iterator<Item = &'p str>,
+        address: u64,
+    ) -> Option<crate::Result<u64>> {
+        let Some(component) = components.next() else {
+            return Some(Ok(address));
+        };
+        let child = match node.child(component) {
+            Ok(Some(child)) => child,
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+        let translated = match Self::translate_address_recursive(child, components, address)? {
+            Ok(translated) => translated,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(child.translate_through_ranges(&node, translated))
+    }
+
     pub(crate) fn read_token(&self, offset: usize) -> Result<FdtToken, FdtError> {
         let val = big_endian::U32::ref_from_prefix(&self.data[offset..])
             .map(|(val, _)| val.get())
-            .map_err(|_e| FdtError::new(FdtErrorKind::InvalidLength, offset))?;
+            .map_err(|_e| FdtError::new(FdtErrorKind::Truncated, offset))?;
         FdtToken::try_from(val).map_err(|t| FdtError::new(FdtErrorKind::BadToken(t), offset))
     }
 
@@ -419,7 +804,7 @@ impl<'a> Fdt<'a> {
         let str_start = str_block_start + string_block_offset;
 
         if str_start >= str_block_end {
-            return Err(FdtError::new(FdtErrorKind::InvalidLength, str_start));
+            return Err(FdtError::new(FdtErrorKind::BadOffset, str_start));
         }
 
         self.string_at_offset(str_start, Some(str_block_end))
@@ -435,7 +820,7 @@ impl<'a> Fdt<'a> {
             Some(end) => self.data.get(offset..end),
             None => self.data.get(offset..),
         };
-        let slice = slice.ok_or(FdtError::new(FdtErrorKind::InvalidOffset, offset))?;
+        let slice = slice.ok_or(FdtError::new(FdtErrorKind::BadOffset, offset))?;
 
         match CStr::from_bytes_until_nul(slice).map(|val| val.to_str()) {
             Ok(Ok(val)) => Ok(val),
@@ -449,7 +834,7 @@ impl<'a> Fdt<'a> {
             match self.data.get(offset) {
                 Some(0) => return Ok(offset + 1),
                 Some(_) => {}
-                None => return Err(FdtError::new(FdtErrorKind::InvalidString, start)),
+                None => return Err(FdtError::new(FdtErrorKind::Truncated, start)),
             }
             offset += 1;
         }
@@ -497,7 +882,7 @@ impl<'a> Fdt<'a> {
     pub(crate) fn next_property_offset(&self, mut offset: usize) -> Result<usize, FdtError> {
         let len = big_endian::U32::ref_from_prefix(&self.data[offset..])
             .map(|(val, _)| val.get())
-            .map_err(|_e| FdtError::new(FdtErrorKind::InvalidLength, offset))?
+            .map_err(|_e| FdtError::new(FdtErrorKind::Truncated, offset))?
             as usize;
         offset += FDT_TAGSIZE; // skip value length
         offset += FDT_TAGSIZE; // skip name offset
@@ -509,6 +894,63 @@ impl<'a> Fdt<'a> {
     pub(crate) fn align_tag_offset(offset: usize) -> usize {
         offset.next_multiple_of(FDT_TAGSIZE)
     }
+
+    /// Returns a wrapper that renders this FDT as YAML matching `dtc -O
+    /// yaml`'s output, for piping into YAML-based schema-validation and
+    /// diff tooling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// println!("{}", fdt.to_yaml());
+    /// ```
+    #[must_use]
+    pub fn to_yaml(&self) -> Yaml<'a> {
+        Yaml { fdt: *self }
+    }
+}
+
+/// Returns whether `node`'s `compatible` property contains any of
+/// `compatibles`.
+fn is_any_compatible(node: &FdtNode<'_>, compatibles: &[&str]) -> Result<bool, FdtError> {
+    Ok(node.compatible()?.any(|c| compatibles.contains(&c)))
+}
+
+/// A view of the `/chosen` node, returned by [`Fdt::chosen`].
+///
+/// `/chosen` carries boot-time parameters the firmware or bootloader hands
+/// to the kernel rather than anything describing the hardware itself, so it
+/// warrants its own accessors instead of requiring every consumer to read
+/// its properties by name.
+#[derive(Debug, Clone, Copy)]
+pub struct Chosen<'a> {
+    node: FdtNode<'a>,
+}
+
+impl<'a> Chosen<'a> {
+    /// Returns the kernel command line, from the `bootargs` property.
+    pub fn bootargs(&self) -> Result<Option<&'a str>, FdtError> {
+        self.string_property("bootargs")
+    }
+
+    /// Returns the path of the node to use for console output, from the
+    /// `stdout-path` property.
+    pub fn stdout_path(&self) -> Result<Option<&'a str>, FdtError> {
+        self.string_property("stdout-path")
+    }
+
+    /// Returns the path of the node to use for console input, from the
+    /// `stdin-path` property.
+    pub fn stdin_path(&self) -> Result<Option<&'a str>, FdtError> {
+        self.string_property("stdin-path")
+    }
+
+    fn string_property(&self, name: &str) -> Result<Option<&'a str>, FdtError> {
+        self.node.property(name)?.map(|prop| prop.as_str()).transpose()
+    }
 }
 
 impl fmt::Display for Fdt<'_> {
@@ -572,14 +1014,14 @@ mod tests {
         let mut header = FDT_HEADER_OK.to_vec();
         header[0] = 0x00;
         let result = Fdt::new(&header);
-        assert!(matches!(result, Err(e) if matches!(e.kind, FdtErrorKind::InvalidMagic)));
+        assert!(matches!(result, Err(e) if matches!(e.kind, FdtErrorKind::BadMagic)));
     }
 
     #[test]
     fn invalid_length() {
         let header = &FDT_HEADER_OK[..10];
         let result = Fdt::new(header);
-        assert!(matches!(result, Err(e) if matches!(e.kind, FdtErrorKind::InvalidLength)));
+        assert!(matches!(result, Err(e) if matches!(e.kind, FdtErrorKind::Truncated)));
     }
 
     #[test]
@@ -587,6 +1029,6 @@ mod tests {
         let mut header = FDT_HEADER_OK.to_vec();
         header[23] = 0x10;
         let result = Fdt::new(&header);
-        assert!(matches!(result, Err(e) if matches!(e.kind, FdtErrorKind::UnsupportedVersion(16))));
+        assert!(matches!(result, Err(e) if matches!(e.kind, FdtErrorKind::BadVersion(16))));
     }
 }
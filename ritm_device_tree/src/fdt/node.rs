@@ -9,8 +9,8 @@
 //! A read-only API for inspecting a device tree node.
 
 use super::{FDT_TAGSIZE, Fdt, FdtToken};
-use crate::error::Error;
-use crate::fdt::property::{FdtPropIter, FdtProperty};
+use crate::error::{FdtError, FdtErrorKind};
+use crate::fdt::property::{FdtPropIter, FdtProperty, RangesIterator, RegIterator};
 use core::fmt;
 
 /// A node in a flattened device tree.
@@ -33,7 +33,7 @@ impl<'a> FdtNode<'a> {
     /// let child = root.child("child1").unwrap().unwrap();
     /// assert_eq!(child.name().unwrap(), "child1");
     /// ```
-    pub fn name(&self) -> Result<&'a str, Error> {
+    pub fn name(&self) -> crate::Result<&'a str> {
         let name_offset = self.offset + FDT_TAGSIZE;
         self.fdt.string_at_offset(name_offset, None)
     }
@@ -142,6 +142,215 @@ impl<'a> FdtNode<'a> {
         }
     }
 
+    /// Returns an iterator over all descendants of this node (not including
+    /// this node itself), in depth-first, document order.
+    ///
+    /// This is the traversal primitive behind whole-tree searches like
+    /// [`Fdt::find_compatible`](super::Fdt::find_compatible) and
+    /// [`Fdt::all_compatible`](super::Fdt::all_compatible); it performs a
+    /// single linear scan through the structure block rather than
+    /// recursing, so it needs no heap allocation to track its position at
+    /// each depth.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_children.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let root = fdt.root().unwrap();
+    /// let names: Vec<_> = root
+    ///     .descendants()
+    ///     .map(|node| node.unwrap().name().unwrap())
+    ///     .collect();
+    /// assert_eq!(names, ["child1", "child2"]);
+    /// ```
+    pub fn descendants(&self) -> impl Iterator<Item = crate::Result<FdtNode<'a>>> + use<'a> {
+        FdtDescendantIter::Start {
+            fdt: self.fdt,
+            offset: self.offset,
+        }
+    }
+
+    /// Returns this node's `#address-cells` value, defaulting to 2 if the
+    /// property is absent.
+    pub fn address_cells(&self) -> crate::Result<u32> {
+        match self.property("#address-cells")? {
+            Some(prop) => prop.as_u32(),
+            None => Ok(2),
+        }
+    }
+
+    /// Returns this node's `#size-cells` value, defaulting to 1 if the
+    /// property is absent.
+    pub fn size_cells(&self) -> crate::Result<u32> {
+        match self.property("#size-cells")? {
+            Some(prop) => prop.as_u32(),
+            None => Ok(1),
+        }
+    }
+
+    /// Returns this node's own `phandle`/`linux,phandle` value, if it has
+    /// one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_phandle.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.node_by_phandle(1).unwrap().unwrap();
+    /// assert_eq!(node.phandle().unwrap(), Some(1));
+    /// ```
+    pub fn phandle(&self) -> crate::Result<Option<u32>> {
+        for prop_name in ["phandle", "linux,phandle"] {
+            if let Some(prop) = self.property(prop_name)? {
+                return Ok(Some(prop.as_phandle()?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Decodes this node's `reg` property, using `parent`'s
+    /// `#address-cells`/`#size-cells` to determine the cell layout.
+    ///
+    /// `reg` is always governed by the node's immediate parent rather than
+    /// some more distant ancestor, so callers already have `parent` in hand
+    /// from whatever traversal (e.g. [`FdtNode::children`]) produced `self`
+    /// in the first place; there's no need for this to walk up the tree
+    /// itself to find it.
+    ///
+    /// Returns `None` if this node has no `reg` property.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_reg_property.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let root = fdt.root().unwrap();
+    /// let child = root.children().next().unwrap().unwrap();
+    /// let mut reg = child.reg(&root).unwrap().unwrap();
+    /// let first = reg.next().unwrap();
+    /// assert_eq!(first.address, 0x1000_0000_2000);
+    /// ```
+    pub fn reg(&self, parent: &FdtNode<'a>) -> crate::Result<Option<RegIterator<'a>>> {
+        let Some(prop) = self.property("reg")? else {
+            return Ok(None);
+        };
+        let address_cells = parent.address_cells()?;
+        let size_cells = parent.size_cells()?;
+        Ok(Some(prop.as_reg(address_cells, size_cells)?))
+    }
+
+    /// Returns an iterator over the strings in this node's `compatible`
+    /// property.
+    ///
+    /// If the node has no `compatible` property, the iterator yields no
+    /// items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_compatible.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/uart").unwrap().unwrap();
+    /// assert!(node.compatible().unwrap().eq(["vendor,uart"]));
+    /// ```
+    pub fn compatible(&self) -> crate::Result<impl Iterator<Item = &'a str> + use<'a>> {
+        Ok(self
+            .property("compatible")?
+            .into_iter()
+            .flat_map(|prop| prop.as_str_list()))
+    }
+
+    /// Returns whether this node's `compatible` property contains `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_compatible.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/uart").unwrap().unwrap();
+    /// assert!(node.is_compatible("vendor,uart").unwrap());
+    /// ```
+    pub fn is_compatible(&self, name: &str) -> crate::Result<bool> {
+        Ok(self.compatible()?.any(|c| c == name))
+    }
+
+    /// Decodes this node's `ranges` property, using `parent`'s
+    /// `#address-cells` for the parent bus address and this node's own
+    /// `#address-cells`/`#size-cells` for the child bus address and length.
+    ///
+    /// Returns `None` if this node has no `ranges` property.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_ranges_property.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let root = fdt.root().unwrap();
+    /// let bus = root.children().next().unwrap().unwrap();
+    /// let mut ranges = bus.ranges(&root).unwrap().unwrap();
+    /// let first = ranges.next().unwrap();
+    /// assert_eq!(first.child_bus_address, 0);
+    /// ```
+    pub fn ranges(&self, parent: &FdtNode<'a>) -> crate::Result<Option<RangesIterator<'a>>> {
+        let Some(prop) = self.property("ranges")? else {
+            return Ok(None);
+        };
+        let child_address_cells = self.address_cells()?;
+        let parent_address_cells = parent.address_cells()?;
+        let size_cells = self.size_cells()?;
+        Ok(Some(prop.as_ranges(
+            child_address_cells,
+            parent_address_cells,
+            size_cells,
+        )?))
+    }
+
+    /// Translates `address`, expressed in this node's own child bus address
+    /// space, into `parent`'s address space using this node's `ranges`
+    /// property.
+    ///
+    /// An empty `ranges` property means this bus is a 1:1 pass-through, so
+    /// `address` is returned unchanged. If this node has no `ranges`
+    /// property at all, it is not memory-mapped onto `parent`, so
+    /// translation fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtErrorKind::NotMemoryMapped`] if this node has no
+    /// `ranges` property, or if `address` does not fall within any of the
+    /// windows a non-empty `ranges` property describes.
+    pub fn translate_through_ranges(
+        &self,
+        parent: &FdtNode<'a>,
+        address: u64,
+    ) -> crate::Result<u64> {
+        let Some(prop) = self.property("ranges")? else {
+            return Err(FdtError::new(FdtErrorKind::NotMemoryMapped, self.offset));
+        };
+        if prop.value().is_empty() {
+            return Ok(address);
+        }
+
+        let ranges = self
+            .ranges(parent)?
+            .expect("the `ranges` property's presence was just checked");
+        for range in ranges {
+            if let Some(offset) = address.checked_sub(range.child_bus_address)
+                && offset < range.length
+            {
+                return Ok(range.parent_bus_address + offset);
+            }
+        }
+        Err(FdtError::new(FdtErrorKind::NotMemoryMapped, self.offset))
+    }
+
     pub(crate) fn fmt_recursive(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
         let name = self.name().map_err(|_| fmt::Error)?;
         if name.is_empty() {
@@ -244,3 +453,98 @@ impl<'a> FdtChildIter<'a> {
         }
     }
 }
+
+/// An iterator over the descendants of a device tree node, in depth-first,
+/// document order.
+///
+/// Unlike [`FdtChildIter`], which steps over each child's subtree via
+/// [`Fdt::next_sibling_offset`], this descends into it: `depth` tracks how
+/// many `FDT_BEGIN_NODE` tags deeper than the starting node the cursor
+/// currently is, so the iterator knows to stop at the `FDT_END_NODE` that
+/// closes the starting node itself, rather than a more deeply nested one.
+enum FdtDescendantIter<'a> {
+    Start { fdt: &'a Fdt<'a>, offset: usize },
+    Running { fdt: &'a Fdt<'a>, offset: usize, depth: usize },
+    Done,
+}
+
+impl<'a> Iterator for FdtDescendantIter<'a> {
+    type Item = crate::Result<FdtNode<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Start { fdt, offset } => {
+                let mut offset = *offset;
+                offset += FDT_TAGSIZE; // Skip FDT_BEGIN_NODE
+                offset = match fdt.find_string_end(offset) {
+                    Ok(offset) => offset,
+                    Err(e) => {
+                        *self = Self::Done;
+                        return Some(Err(e));
+                    }
+                };
+                offset = Fdt::align_tag_offset(offset);
+                *self = Self::Running { fdt, offset, depth: 0 };
+                self.next()
+            }
+            Self::Running { fdt, offset, depth } => match Self::try_next(fdt, offset, depth) {
+                Some(Ok(val)) => Some(Ok(val)),
+                Some(Err(e)) => {
+                    *self = Self::Done;
+                    Some(Err(e))
+                }
+                None => {
+                    *self = Self::Done;
+                    None
+                }
+            },
+            Self::Done => None,
+        }
+    }
+}
+
+impl<'a> FdtDescendantIter<'a> {
+    fn try_next(
+        fdt: &'a Fdt<'a>,
+        offset: &mut usize,
+        depth: &mut usize,
+    ) -> Option<crate::Result<FdtNode<'a>>> {
+        loop {
+            let token = match fdt.read_token(*offset) {
+                Ok(token) => token,
+                Err(e) => return Some(Err(e)),
+            };
+            match token {
+                FdtToken::BeginNode => {
+                    let node_offset = *offset;
+                    *depth += 1;
+                    let mut node_end = node_offset + FDT_TAGSIZE;
+                    node_end = match fdt.find_string_end(node_end) {
+                        Ok(node_end) => node_end,
+                        Err(e) => return Some(Err(e)),
+                    };
+                    *offset = Fdt::align_tag_offset(node_end);
+                    return Some(Ok(FdtNode {
+                        fdt,
+                        offset: node_offset,
+                    }));
+                }
+                FdtToken::EndNode => {
+                    *offset += FDT_TAGSIZE;
+                    match depth.checked_sub(1) {
+                        Some(remaining) => *depth = remaining,
+                        None => return None,
+                    }
+                }
+                FdtToken::Prop => {
+                    *offset = match fdt.next_property_offset(*offset + FDT_TAGSIZE) {
+                        Ok(offset) => offset,
+                        Err(e) => return Some(Err(e)),
+                    };
+                }
+                FdtToken::Nop => *offset += FDT_TAGSIZE,
+                _ => return None,
+            }
+        }
+    }
+}
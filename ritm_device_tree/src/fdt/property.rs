@@ -24,6 +24,35 @@ pub struct FdtProperty<'a> {
     value_offset: usize,
 }
 
+/// A typed view of a property's value, classified using the same heuristics
+/// [`FdtProperty`]'s `Display` rendering uses.
+///
+/// This lets callers match on the value's likely shape instead of
+/// speculatively calling [`FdtProperty::as_u32`]/[`FdtProperty::as_str`]/etc.
+/// and discarding the error on a mismatch. [`FdtProperty::value`] remains
+/// available as a raw fallback regardless of how a value classifies.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PropertyValue<'a> {
+    /// The value is empty, e.g. a boolean property like `foo;`.
+    Empty,
+    /// The value is exactly 4 bytes, interpreted as a big-endian `u32`.
+    U32(u32),
+    /// The value is exactly 8 bytes, interpreted as a big-endian `u64`.
+    U64(u64),
+    /// The value is a single NUL-terminated, printable string.
+    Str(&'a str),
+    /// The value is more than one NUL-terminated, printable string
+    /// concatenated together.
+    StrList(FdtStringListIterator<'a>),
+    /// The value's length is a multiple of 4 bytes but isn't 4 or 8, e.g. a
+    /// cell array like `interrupts` or `clocks`. See
+    /// [`FdtProperty::as_cells`] to decode it.
+    Cells(&'a [u8]),
+    /// The value didn't match any of the above; opaque raw bytes.
+    Bytes(&'a [u8]),
+}
+
 impl<'a> FdtProperty<'a> {
     /// Returns the name of this property.
     #[must_use]
@@ -40,7 +69,7 @@ impl<'a> FdtProperty<'a> {
     ///
     /// # Errors
     ///
-    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// Returns an [`FdtErrorKind::Truncated`] if the property's value is
     /// not 4 bytes long.
     ///
     /// # Examples
@@ -56,14 +85,14 @@ impl<'a> FdtProperty<'a> {
     pub fn as_u32(&self) -> Result<u32, FdtError> {
         big_endian::U32::ref_from_bytes(self.value)
             .map(|val| val.get())
-            .map_err(|_e| FdtError::new(FdtErrorKind::InvalidLength, self.value_offset))
+            .map_err(|_e| FdtError::new(FdtErrorKind::Truncated, self.value_offset))
     }
 
     /// Returns the value of this property as a `u64`.
     ///
     /// # Errors
     ///
-    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// Returns an [`FdtErrorKind::Truncated`] if the property's value is
     /// not 8 bytes long.
     ///
     /// # Examples
@@ -79,7 +108,25 @@ impl<'a> FdtProperty<'a> {
     pub fn as_u64(&self) -> Result<u64, FdtError> {
         big_endian::U64::ref_from_bytes(self.value)
             .map(|val| val.get())
-            .map_err(|_e| FdtError::new(FdtErrorKind::InvalidLength, self.value_offset))
+            .map_err(|_e| FdtError::new(FdtErrorKind::Truncated, self.value_offset))
+    }
+
+    /// Returns the value of this property as a phandle id.
+    ///
+    /// Like [`FdtProperty::as_u32`], but additionally rejects the values `0`
+    /// and `0xffffffff`, which the Devicetree specification reserves and
+    /// which therefore never identify a real node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::Truncated`] if the property's value is not
+    /// 4 bytes long, or an [`FdtErrorKind::BadPhandle`] if it holds a
+    /// reserved phandle value.
+    pub fn as_phandle(&self) -> Result<u32, FdtError> {
+        match self.as_u32()? {
+            0 | 0xffff_ffff => Err(FdtError::new(FdtErrorKind::BadPhandle, self.value_offset)),
+            phandle => Ok(phandle),
+        }
     }
 
     /// Returns the value of this property as a string.
@@ -126,12 +173,179 @@ impl<'a> FdtProperty<'a> {
         FdtStringListIterator { value: self.value }
     }
 
-    pub(crate) fn fmt(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
-        write!(f, "{:indent$}{}", "", self.name, indent = indent)?;
+    /// Returns the value of this property decoded as a `reg` property.
+    ///
+    /// `reg` is a flat array of cells where each entry consists of
+    /// `address_cells` big-endian `u32` cells followed by `size_cells`
+    /// big-endian `u32` cells. `address_cells` and `size_cells` normally come
+    /// from the `#address-cells`/`#size-cells` properties of the parent node;
+    /// see [`FdtNode::reg`] for a convenience that looks those up
+    /// automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if `address_cells` or
+    /// `size_cells` is greater than 2 (which wouldn't fit in a `u64`), or if
+    /// the property's value is not a multiple of `(address_cells +
+    /// size_cells) * 4` bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_reg_property.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-reg").unwrap().unwrap();
+    /// let prop = node.property("reg").unwrap().unwrap();
+    /// let mut reg = prop.as_reg(2, 1).unwrap();
+    /// let first = reg.next().unwrap();
+    /// assert_eq!(first.address, 0x1000_0000_2000);
+    /// assert_eq!(first.size, Some(0x100));
+    /// ```
+    pub fn as_reg(
+        &self,
+        address_cells: u32,
+        size_cells: u32,
+    ) -> Result<RegIterator<'a>, FdtError> {
+        if address_cells > 2 || size_cells > 2 {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, self.value_offset));
+        }
+        let entry_size = (address_cells + size_cells) as usize * 4;
+        if entry_size == 0 || !self.value.len().is_multiple_of(entry_size) {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, self.value_offset));
+        }
+        Ok(RegIterator {
+            value: self.value,
+            address_cells,
+            size_cells,
+        })
+    }
 
+    /// Returns the value of this property decoded as a `ranges` property.
+    ///
+    /// `ranges` is a flat array of cells where each entry consists of a child
+    /// bus address (`child_address_cells` cells, i.e. this node's own
+    /// `#address-cells`), a parent bus address (`parent_address_cells`
+    /// cells, i.e. the parent node's `#address-cells`), and a length
+    /// (`size_cells` cells, i.e. this node's own `#size-cells`). See
+    /// [`FdtNode::ranges`] for a convenience that looks those cell counts up
+    /// automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if any cell count is
+    /// greater than 2 (which wouldn't fit in a `u64`), or if the property's
+    /// value is not a multiple of `(child_address_cells +
+    /// parent_address_cells + size_cells) * 4` bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_ranges_property.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/soc/bus").unwrap().unwrap();
+    /// let prop = node.property("ranges").unwrap().unwrap();
+    /// let mut ranges = prop.as_ranges(2, 2, 1).unwrap();
+    /// let first = ranges.next().unwrap();
+    /// assert_eq!(first.length, 0x1000);
+    /// ```
+    pub fn as_ranges(
+        &self,
+        child_address_cells: u32,
+        parent_address_cells: u32,
+        size_cells: u32,
+    ) -> Result<RangesIterator<'a>, FdtError> {
+        if child_address_cells > 2 || parent_address_cells > 2 || size_cells > 2 {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, self.value_offset));
+        }
+        let entry_size = (child_address_cells + parent_address_cells + size_cells) as usize * 4;
+        if entry_size == 0 || !self.value.len().is_multiple_of(entry_size) {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, self.value_offset));
+        }
+        Ok(RangesIterator {
+            value: self.value,
+            child_address_cells,
+            parent_address_cells,
+            size_cells,
+        })
+    }
+
+    /// Returns an iterator over the big-endian `u32` cells in this
+    /// property's value.
+    ///
+    /// This is the primitive used to decode cell arrays like `interrupts`,
+    /// `clocks`, or `gpios` without reimplementing the chunking logic.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value
+    /// length is not a multiple of 4 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// let prop = node.property("u32-prop").unwrap().unwrap();
+    /// let mut cells = prop.as_cells().unwrap();
+    /// assert_eq!(cells.next(), Some(0x12345678));
+    /// ```
+    pub fn as_cells(&self) -> Result<CellIterator<'a>, FdtError> {
+        if !self.value.len().is_multiple_of(4) {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, self.value_offset));
+        }
+        Ok(CellIterator { value: self.value })
+    }
+
+    /// Returns an iterator over the big-endian `u64`s formed by folding
+    /// consecutive pairs of cells in this property's value, most-significant
+    /// cell first.
+    ///
+    /// This is useful for properties like `interrupts-extended` entries or
+    /// `reg`-like values that are made up of two-cell 64-bit quantities.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value
+    /// length is not a multiple of 8 bytes.
+    pub fn as_u64_pairs(&self) -> Result<impl Iterator<Item = u64> + 'a, FdtError> {
+        if !self.value.len().is_multiple_of(8) {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, self.value_offset));
+        }
+        let mut cells = self
+            .as_cells()
+            .expect("length was just validated to be a multiple of 8, hence of 4");
+        Ok(core::iter::from_fn(move || {
+            let high = cells.next()?;
+            let low = cells.next()?;
+            Some((u64::from(high) << 32) | u64::from(low))
+        }))
+    }
+
+    /// Classifies this property's value, using the same heuristics its
+    /// `Display` rendering does: all bytes printable ASCII or NUL,
+    /// NUL-terminated, and no embedded empty string classifies it as a
+    /// string or string list; otherwise a length that's a multiple of 4
+    /// bytes classifies it as `U32`/`U64`/`Cells`; anything else is raw
+    /// bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::{Fdt, PropertyValue};
+    /// # let dtb = include_bytes!("../../dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// let prop = node.property("str-prop").unwrap().unwrap();
+    /// assert_eq!(prop.parsed(), PropertyValue::Str("hello world"));
+    /// ```
+    #[must_use]
+    pub fn parsed(&self) -> PropertyValue<'a> {
         if self.value.is_empty() {
-            writeln!(f, ";")?;
-            return Ok(());
+            return PropertyValue::Empty;
         }
 
         let is_printable = self
@@ -142,41 +356,60 @@ impl<'a> FdtProperty<'a> {
         if is_printable && self.value.ends_with(&[0]) && !has_empty {
             let mut strings = self.as_str_list();
             if let Some(first) = strings.next() {
-                write!(f, " = \"{first}\"")?;
-                for s in strings {
-                    write!(f, ", \"{s}\"")?;
-                }
-                writeln!(f, ";")?;
-                return Ok(());
+                return if strings.next().is_some() {
+                    PropertyValue::StrList(FdtStringListIterator { value: self.value })
+                } else {
+                    PropertyValue::Str(first)
+                };
             }
         }
 
-        if self.value.len().is_multiple_of(4) {
-            write!(f, " = <")?;
-            for (i, chunk) in self.value.chunks_exact(4).enumerate() {
-                if i > 0 {
-                    write!(f, " ")?;
+        match self.value.len() {
+            4 => PropertyValue::U32(self.as_u32().expect("length just checked")),
+            8 => PropertyValue::U64(self.as_u64().expect("length just checked")),
+            len if len.is_multiple_of(4) => PropertyValue::Cells(self.value),
+            _ => PropertyValue::Bytes(self.value),
+        }
+    }
+
+    pub(crate) fn fmt(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.name, indent = indent)?;
+
+        match self.parsed() {
+            PropertyValue::Empty => writeln!(f, ";"),
+            PropertyValue::Str(s) => writeln!(f, " = \"{s}\";"),
+            PropertyValue::StrList(strings) => {
+                write!(f, " = ")?;
+                for (i, s) in strings.enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{s}\"")?;
                 }
-                let val = u32::from_be_bytes(
-                    chunk
-                        .try_into()
-                        .expect("u32::from_be_bytes() should always succeed with 4 bytes"),
-                );
-                write!(f, "0x{val:02x}")?;
+                writeln!(f, ";")
             }
-            writeln!(f, ">;")?;
-        } else {
-            write!(f, " = [")?;
-            for (i, byte) in self.value.iter().enumerate() {
-                if i > 0 {
-                    write!(f, " ")?;
+            PropertyValue::U32(_) | PropertyValue::U64(_) | PropertyValue::Cells(_) => {
+                write!(f, " = <")?;
+                let cells = self.as_cells().expect("length is a multiple of 4");
+                for (i, val) in cells.enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "0x{val:02x}")?;
+                }
+                writeln!(f, ">;")
+            }
+            PropertyValue::Bytes(bytes) => {
+                write!(f, " = [")?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{byte:02x}")?;
                 }
-                write!(f, "{byte:02x}")?;
+                writeln!(f, "];")
             }
-            writeln!(f, "];")?;
         }
-
-        Ok(())
     }
 }
 
@@ -233,7 +466,7 @@ impl<'a> FdtPropIter<'a> {
                     ) {
                         Ok((val, _)) => val.get() as usize,
                         Err(_) => {
-                            return Some(Err(FdtError::new(FdtErrorKind::InvalidLength, *offset)));
+                            return Some(Err(FdtError::new(FdtErrorKind::Truncated, *offset)));
                         }
                     };
                     let nameoff = match big_endian::U32::ref_from_prefix(
@@ -241,7 +474,7 @@ impl<'a> FdtPropIter<'a> {
                     ) {
                         Ok((val, _)) => val.get() as usize,
                         Err(_) => {
-                            return Some(Err(FdtError::new(FdtErrorKind::InvalidLength, *offset)));
+                            return Some(Err(FdtError::new(FdtErrorKind::Truncated, *offset)));
                         }
                     };
                     let prop_offset = *offset + 3 * FDT_TAGSIZE;
@@ -250,7 +483,12 @@ impl<'a> FdtPropIter<'a> {
                         Ok(name) => name,
                         Err(e) => return Some(Err(e)),
                     };
-                    let value = fdt.data.get(prop_offset..prop_offset + len)?;
+                    let value = match fdt.data.get(prop_offset..prop_offset + len) {
+                        Some(value) => value,
+                        None => {
+                            return Some(Err(FdtError::new(FdtErrorKind::Truncated, prop_offset)));
+                        }
+                    };
                     return Some(Ok(FdtProperty {
                         name,
                         value,
@@ -264,7 +502,123 @@ impl<'a> FdtPropIter<'a> {
     }
 }
 
-struct FdtStringListIterator<'a> {
+/// An iterator over the big-endian `u32` cells in a property's value,
+/// returned by [`FdtProperty::as_cells`].
+#[derive(Debug, Clone)]
+pub struct CellIterator<'a> {
+    value: &'a [u8],
+}
+
+impl Iterator for CellIterator<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (cell, rest) = self.value.split_first_chunk::<4>()?;
+        self.value = rest;
+        Some(u32::from_be_bytes(*cell))
+    }
+}
+
+/// A decoded entry of a `reg` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reg {
+    /// The address of this entry, in the parent bus's address space.
+    pub address: u64,
+    /// The size of this entry, or `None` if the node's `#size-cells` is 0.
+    pub size: Option<u64>,
+}
+
+/// An iterator over the entries of a `reg` property, returned by
+/// [`FdtProperty::as_reg`].
+#[derive(Debug, Clone)]
+pub struct RegIterator<'a> {
+    value: &'a [u8],
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl Iterator for RegIterator<'_> {
+    type Item = Reg;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.value.is_empty() {
+            return None;
+        }
+
+        let (address, rest) = fold_cells(self.value, self.address_cells);
+        let (size, rest) = if self.size_cells == 0 {
+            (None, rest)
+        } else {
+            let (size, rest) = fold_cells(rest, self.size_cells);
+            (Some(size), rest)
+        };
+        self.value = rest;
+
+        Some(Reg { address, size })
+    }
+}
+
+/// A decoded entry of a `ranges` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressRange {
+    /// The start address of this range, in the child bus's address space.
+    pub child_bus_address: u64,
+    /// The start address of this range, in the parent bus's address space.
+    pub parent_bus_address: u64,
+    /// The length of this range.
+    pub length: u64,
+}
+
+/// An iterator over the entries of a `ranges` property, returned by
+/// [`FdtProperty::as_ranges`].
+#[derive(Debug, Clone)]
+pub struct RangesIterator<'a> {
+    value: &'a [u8],
+    child_address_cells: u32,
+    parent_address_cells: u32,
+    size_cells: u32,
+}
+
+impl Iterator for RangesIterator<'_> {
+    type Item = AddressRange;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.value.is_empty() {
+            return None;
+        }
+
+        let (child_bus_address, rest) = fold_cells(self.value, self.child_address_cells);
+        let (parent_bus_address, rest) = fold_cells(rest, self.parent_address_cells);
+        let (length, rest) = fold_cells(rest, self.size_cells);
+        self.value = rest;
+
+        Some(AddressRange {
+            child_bus_address,
+            parent_bus_address,
+            length,
+        })
+    }
+}
+
+/// Reads `cells` consecutive big-endian `u32`s from the front of `value` and
+/// folds them into a `u64`, most-significant cell first. Returns the value
+/// and the remaining slice.
+fn fold_cells(value: &[u8], cells: u32) -> (u64, &[u8]) {
+    let mut result = 0u64;
+    let mut rest = value;
+    for _ in 0..cells {
+        let (cell, remainder) =
+            big_endian::U32::ref_from_prefix(rest).expect("length was validated by as_reg/as_ranges");
+        result = (result << 32) | u64::from(cell.get());
+        rest = remainder;
+    }
+    (result, rest)
+}
+
+/// An iterator over the strings in a property's value, returned by
+/// [`FdtProperty::as_str_list`] and held by [`PropertyValue::StrList`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FdtStringListIterator<'a> {
     value: &'a [u8],
 }
 
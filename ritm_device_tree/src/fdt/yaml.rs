@@ -0,0 +1,112 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A `dtc -O yaml`-compatible YAML rendering of an [`Fdt`].
+
+use core::fmt;
+
+use super::property::{FdtProperty, PropertyValue};
+use super::{Fdt, FdtNode};
+
+/// Renders an [`Fdt`] as YAML matching `dtc -O yaml`'s output, via its
+/// [`Display`](fmt::Display) implementation.
+///
+/// Returned by [`Fdt::to_yaml`]. This walks the same structure
+/// [`Fdt`]'s own `Display` impl does, just with a different per-node and
+/// per-property emitter, so the resulting document can be piped into
+/// YAML-based schema validation and diff tooling the way a DTS dump can't.
+#[derive(Debug, Clone, Copy)]
+pub struct Yaml<'a> {
+    pub(super) fdt: Fdt<'a>,
+}
+
+impl fmt::Display for Yaml<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---")?;
+        let root = self.fdt.root().map_err(|_| fmt::Error)?;
+        fmt_node(f, &root, "/", 0)
+    }
+}
+
+/// Renders `node` (named `name`) and its subtree as a nested YAML mapping.
+fn fmt_node(f: &mut fmt::Formatter<'_>, node: &FdtNode<'_>, name: &str, indent: usize) -> fmt::Result {
+    write!(f, "{:indent$}", "", indent = indent)?;
+    fmt_key(f, name)?;
+    writeln!(f, ":")?;
+
+    for prop in node.properties() {
+        let prop = prop.map_err(|_| fmt::Error)?;
+        write!(f, "{:indent$}  ", "", indent = indent)?;
+        fmt_key(f, prop.name())?;
+        write!(f, ": ")?;
+        fmt_value(f, &prop)?;
+        writeln!(f)?;
+    }
+
+    for child in node.children() {
+        let child = child.map_err(|_| fmt::Error)?;
+        let child_name = child.name().map_err(|_| fmt::Error)?;
+        fmt_node(f, &child, child_name, indent + 2)?;
+    }
+
+    Ok(())
+}
+
+/// Renders `name` as a YAML mapping key, quoting it if it would otherwise be
+/// read back as something other than a plain scalar string (e.g.
+/// `#address-cells`, whose leading `#` YAML would otherwise parse as a
+/// comment).
+fn fmt_key(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    if name.starts_with('#') {
+        write!(f, "'{name}'")
+    } else {
+        write!(f, "{name}")
+    }
+}
+
+/// Renders `prop`'s value the way `dtc -O yaml` does: a boolean property
+/// becomes `true`, strings become a quoted flow sequence, cell arrays become
+/// a flow sequence containing a single nested flow sequence of the cells,
+/// and anything else becomes a flow sequence of hex bytes.
+fn fmt_value(f: &mut fmt::Formatter<'_>, prop: &FdtProperty<'_>) -> fmt::Result {
+    match prop.parsed() {
+        PropertyValue::Empty => write!(f, "true"),
+        PropertyValue::Str(s) => write!(f, "[\"{s}\"]"),
+        PropertyValue::StrList(strings) => {
+            write!(f, "[")?;
+            for (i, s) in strings.enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "\"{s}\"")?;
+            }
+            write!(f, "]")
+        }
+        PropertyValue::U32(_) | PropertyValue::U64(_) | PropertyValue::Cells(_) => {
+            write!(f, "[[")?;
+            let cells = prop.as_cells().expect("classified as cells above");
+            for (i, cell) in cells.enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{cell:#x}")?;
+            }
+            write!(f, "]]")
+        }
+        PropertyValue::Bytes(bytes) => {
+            write!(f, "[")?;
+            for (i, byte) in bytes.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{byte:#04x}")?;
+            }
+            write!(f, "]")
+        }
+    }
+}
@@ -11,9 +11,12 @@
 //! This library provides a comprehensive API for working with FDTs, including:
 //!
 //! - A read-only API for parsing and traversing FDTs without memory allocation.
+//! - A no-alloc builder for constructing FDTs directly into a byte buffer.
 //! - A read-write API for creating and modifying FDTs in memory.
 //! - Support for applying device tree overlays.
 //! - Outputting device trees in DTS source format.
+//! - Optional `serde` snapshot serialization of a [`DeviceTree`](model::DeviceTree),
+//!   for saving and restoring one outside of the DTB format.
 //!
 //! The library is written purely in Rust and is `#![no_std]` compatible. If
 //! you don't need the Device Tree manipulation functionality, the library is
@@ -88,6 +91,7 @@ pub mod memreserve;
 extern crate alloc;
 
 pub mod error;
+pub use error::Result;
 pub mod fdt;
 #[cfg(feature = "write")]
 #[cfg_attr(docsrs, doc(cfg(feature = "write")))]
@@ -10,6 +10,7 @@
 
 /// A 64-bit memory reservation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryReservation {
     address: u64,
     size: u64,
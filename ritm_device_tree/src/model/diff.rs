@@ -0,0 +1,246 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A semantic diff between two [`DeviceTree`]s.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::MemoryReservation;
+
+impl DeviceTree {
+    /// Computes a semantic diff between this tree and `other`, the way
+    /// `dtc`'s `dtdiff` compares normalized trees rather than comparing
+    /// serialized blobs byte-for-byte.
+    ///
+    /// The comparison is canonical: children and properties are matched by
+    /// name rather than position, so two trees that differ only in
+    /// serialization order produce an empty [`TreeDiff`].
+    #[must_use]
+    pub fn diff(&self, other: &DeviceTree) -> TreeDiff {
+        let (removed_memory_reservations, added_memory_reservations) =
+            diff_memory_reservations(&self.memory_reservations, &other.memory_reservations);
+        TreeDiff {
+            root: diff_nodes("/", self.root(), other.root()),
+            removed_memory_reservations,
+            added_memory_reservations,
+        }
+    }
+}
+
+/// A semantic diff between two [`DeviceTree`]s, returned by
+/// [`DeviceTree::diff`].
+#[derive(Debug, Clone)]
+pub struct TreeDiff {
+    root: NodeDiff,
+    /// Memory reservations present only in the left-hand tree.
+    pub removed_memory_reservations: Vec<MemoryReservation>,
+    /// Memory reservations present only in the right-hand tree.
+    pub added_memory_reservations: Vec<MemoryReservation>,
+}
+
+impl TreeDiff {
+    /// Returns `true` if the two trees compared equal: no differences were
+    /// found anywhere in the tree or in its memory reservations.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.root.is_empty()
+            && self.removed_memory_reservations.is_empty()
+            && self.added_memory_reservations.is_empty()
+    }
+
+    /// Returns the diff of the root node, recursing into every node common
+    /// to both trees.
+    #[must_use]
+    pub fn root(&self) -> &NodeDiff {
+        &self.root
+    }
+}
+
+/// The diff of a single node common to both trees, recursing into its
+/// common children. Returned by [`TreeDiff::root`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct NodeDiff {
+    path: String,
+    /// Properties present only in the left-hand tree.
+    pub removed_properties: Vec<DeviceTreeProperty>,
+    /// Properties present only in the right-hand tree.
+    pub added_properties: Vec<DeviceTreeProperty>,
+    /// Properties present in both trees with different values, as `(old,
+    /// new)` pairs.
+    pub changed_properties: Vec<(DeviceTreeProperty, DeviceTreeProperty)>,
+    /// Child nodes present only in the left-hand tree.
+    pub removed_children: Vec<DeviceTreeNode>,
+    /// Child nodes present only in the right-hand tree.
+    pub added_children: Vec<DeviceTreeNode>,
+    /// Diffs of children present, by name, in both trees, omitting any pair
+    /// of children that compared equal.
+    pub changed_children: Vec<NodeDiff>,
+}
+
+impl NodeDiff {
+    /// Returns the path of the node this diff describes.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns `true` if this node and all of its common children compared
+    /// equal.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.removed_properties.is_empty()
+            && self.added_properties.is_empty()
+            && self.changed_properties.is_empty()
+            && self.removed_children.is_empty()
+            && self.added_children.is_empty()
+            && self.changed_children.is_empty()
+    }
+}
+
+fn diff_nodes(path: &str, left: &DeviceTreeNode, right: &DeviceTreeNode) -> NodeDiff {
+    let mut removed_properties = Vec::new();
+    let mut changed_properties = Vec::new();
+    for property in left.properties() {
+        match right.property(property.name()) {
+            None => removed_properties.push(property.clone()),
+            Some(other) if other.value() != property.value() => {
+                changed_properties.push((property.clone(), other.clone()));
+            }
+            Some(_) => {}
+        }
+    }
+    let added_properties = right
+        .properties()
+        .filter(|property| left.property(property.name()).is_none())
+        .cloned()
+        .collect();
+
+    let mut removed_children = Vec::new();
+    let mut changed_children = Vec::new();
+    for child in left.children() {
+        match right.child(child.name()) {
+            None => removed_children.push(child.clone()),
+            Some(other) => {
+                let child_diff = diff_nodes(&join_path(path, child.name()), child, other);
+                if !child_diff.is_empty() {
+                    changed_children.push(child_diff);
+                }
+            }
+        }
+    }
+    let added_children = right
+        .children()
+        .filter(|child| left.child(child.name()).is_none())
+        .cloned()
+        .collect();
+
+    NodeDiff {
+        path: path.to_string(),
+        removed_properties,
+        added_properties,
+        changed_properties,
+        removed_children,
+        added_children,
+        changed_children,
+    }
+}
+
+/// Splits `left` and `right` into the reservations only `left` has and the
+/// reservations only `right` has, treating both as multisets (so a
+/// reservation appearing twice in `left` and once in `right` counts as one
+/// removal).
+fn diff_memory_reservations(
+    left: &[MemoryReservation],
+    right: &[MemoryReservation],
+) -> (Vec<MemoryReservation>, Vec<MemoryReservation>) {
+    let mut right_remaining = right.to_vec();
+    let mut removed = Vec::new();
+    for reservation in left {
+        match right_remaining.iter().position(|candidate| candidate == reservation) {
+            Some(index) => {
+                right_remaining.remove(index);
+            }
+            None => removed.push(*reservation),
+        }
+    }
+    (removed, right_remaining)
+}
+
+/// Joins a node's own path with a child's name, avoiding a doubled `/` when
+/// `parent` is the root.
+fn join_path(parent: &str, name: &str) -> String {
+    if parent == "/" {
+        alloc::format!("/{name}")
+    } else {
+        alloc::format!("{parent}/{name}")
+    }
+}
+
+impl fmt::Display for TreeDiff {
+    /// Renders this diff as a DTS-style unified diff: unchanged lines are
+    /// prefixed with a space, removed lines with `-`, and added lines with
+    /// `+`, reusing [`DeviceTreeProperty`]'s own `Display` rendering for
+    /// property lines.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for reservation in &self.removed_memory_reservations {
+            writeln!(f, "-/memreserve/ {:#x} {:#x};", reservation.address(), reservation.size())?;
+        }
+        for reservation in &self.added_memory_reservations {
+            writeln!(f, "+/memreserve/ {:#x} {:#x};", reservation.address(), reservation.size())?;
+        }
+        fmt_node_diff(&self.root, f, 0)
+    }
+}
+
+fn fmt_node_diff(diff: &NodeDiff, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+    writeln!(f, " {:indent$}{} {{", "", node_label(diff.path()), indent = indent)?;
+    for property in &diff.removed_properties {
+        writeln!(f, "-{:indent$}    {property}", "", indent = indent)?;
+    }
+    for (old, new) in &diff.changed_properties {
+        writeln!(f, "-{:indent$}    {old}", "", indent = indent)?;
+        writeln!(f, "+{:indent$}    {new}", "", indent = indent)?;
+    }
+    for property in &diff.added_properties {
+        writeln!(f, "+{:indent$}    {property}", "", indent = indent)?;
+    }
+    for child in &diff.removed_children {
+        fmt_full_node(child, f, indent + 4, '-')?;
+    }
+    for child_diff in &diff.changed_children {
+        fmt_node_diff(child_diff, f, indent + 4)?;
+    }
+    for child in &diff.added_children {
+        fmt_full_node(child, f, indent + 4, '+')?;
+    }
+    writeln!(f, " {:indent$}}};", "", indent = indent)
+}
+
+/// Renders `node` (and all of its descendants) as a block of lines all
+/// prefixed with `sign`, for a node that exists in only one of the two
+/// trees being diffed.
+fn fmt_full_node(node: &DeviceTreeNode, f: &mut fmt::Formatter<'_>, indent: usize, sign: char) -> fmt::Result {
+    writeln!(f, "{sign}{:indent$}{} {{", "", node.name(), indent = indent)?;
+    for property in node.properties() {
+        writeln!(f, "{sign}{:indent$}    {property}", "", indent = indent)?;
+    }
+    for child in node.children() {
+        fmt_full_node(child, f, indent + 4, sign)?;
+    }
+    writeln!(f, "{sign}{:indent$}}};", "", indent = indent)
+}
+
+/// Returns the final path component of `path` (the node's own name), or
+/// `"/"` for the root.
+fn node_label(path: &str) -> &str {
+    path.rsplit('/').find(|s| !s.is_empty()).unwrap_or("/")
+}
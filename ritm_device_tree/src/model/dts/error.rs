@@ -0,0 +1,82 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Error types for the `ritm_device_tree` crate.
+
+use alloc::string::String;
+use core::fmt;
+
+/// An error that can occur when parsing devicetree source (DTS) text.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct DtsError {
+    offset: usize,
+    /// The type of the error that has occurred.
+    pub kind: DtsErrorKind,
+}
+
+impl DtsError {
+    pub(crate) fn new(kind: DtsErrorKind, offset: usize) -> Self {
+        Self { offset, kind }
+    }
+
+    /// Returns the byte offset into the source text at which the error was
+    /// detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// The kind of an error that can occur when parsing devicetree source (DTS)
+/// text.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DtsErrorKind {
+    /// The input ended before a structurally required token could be read.
+    UnexpectedEof,
+    /// A token was found where it did not belong (e.g. a missing `;` or
+    /// `{`).
+    UnexpectedToken,
+    /// A numeric literal was malformed or out of range.
+    InvalidNumber,
+    /// A `"..."` string literal was never closed.
+    UnterminatedString,
+    /// A `/* ... */` comment was never closed.
+    UnterminatedComment,
+    /// A `[ ... ]` byte string literal held something other than pairs of
+    /// hex digits.
+    InvalidByteString,
+    /// A `&label` reference did not match any labeled node in the source.
+    UnknownLabel(String),
+    /// The source text had no root (`/ { ... };`) node.
+    MissingRoot,
+}
+
+impl fmt::Display for DtsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte offset {}", self.kind, self.offset)
+    }
+}
+
+impl fmt::Display for DtsErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::UnexpectedToken => write!(f, "unexpected token"),
+            Self::InvalidNumber => write!(f, "invalid numeric literal"),
+            Self::UnterminatedString => write!(f, "unterminated string literal"),
+            Self::UnterminatedComment => write!(f, "unterminated block comment"),
+            Self::InvalidByteString => write!(f, "invalid byte string literal"),
+            Self::UnknownLabel(label) => write!(f, "reference to undefined label `{label}`"),
+            Self::MissingRoot => write!(f, "no root node (`/ {{ ... }};`) found"),
+        }
+    }
+}
+
+impl core::error::Error for DtsError {}
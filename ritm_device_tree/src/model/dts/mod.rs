@@ -0,0 +1,538 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A parser for devicetree source (DTS) text.
+
+use alloc::collections::btree_map::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::MemoryReservation;
+
+mod error;
+pub use error::{DtsError, DtsErrorKind};
+
+impl DeviceTree {
+    /// Parses devicetree source (DTS) text into a `DeviceTree`, the inverse
+    /// of the `Display` impls [`Fdt`](crate::fdt::Fdt) and `DeviceTree`
+    /// themselves provide.
+    ///
+    /// Supports the core DTS grammar: an optional `/dts-v1/;` header,
+    /// `/memreserve/ <addr> <size>;` directives, node blocks with optional
+    /// `label:` prefixes, the three property value forms the writer itself
+    /// emits (comma-separated string lists, `<cell>` arrays, and `[byte]`
+    /// strings, which may be mixed within a single assignment), boolean
+    /// (valueless) properties, `&label`/`&{/path}` phandle references inside
+    /// cell arrays, `/delete-node/`/`/delete-property/` directives, and
+    /// `//`/`/* */` comments.
+    ///
+    /// A `&label`/`&{/path}` reference allocates a fresh `phandle` property
+    /// on the referenced node if it doesn't already have one, mirroring how
+    /// [`DeviceTree::apply_overlay`] resolves `__fixups__` entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DtsError`] if the source text is malformed, or if a
+    /// `&label` reference or a `/delete-node/ &label;` directive names a
+    /// label with no matching node in the source.
+    pub fn from_dts(source: &str) -> Result<Self, DtsError> {
+        Parser::new(source).parse_tree()
+    }
+}
+
+/// A phandle cell in a `<...>` array left as a placeholder by a `&label`
+/// reference, to be patched once the whole tree (and hence every label's
+/// path) is known.
+struct PendingRef {
+    node_path: String,
+    prop_name: String,
+    cell_offset: usize,
+    label: String,
+}
+
+/// A `/delete-node/ &label;` directive, deferred for the same reason as
+/// [`PendingRef`].
+struct PendingDelete {
+    label: String,
+}
+
+enum DeleteTarget {
+    Name(String),
+    Label(String),
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    /// Maps each `label:`-prefixed node to its absolute path.
+    labels: BTreeMap<String, String>,
+    pending_refs: Vec<PendingRef>,
+    pending_deletes: Vec<PendingDelete>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            pos: 0,
+            labels: BTreeMap::new(),
+            pending_refs: Vec::new(),
+            pending_deletes: Vec::new(),
+        }
+    }
+
+    fn parse_tree(mut self) -> Result<DeviceTree, DtsError> {
+        self.skip_trivia()?;
+        if self.eat_str("/dts-v1/")? {
+            self.expect_char(';')?;
+        }
+
+        let mut memory_reservations = Vec::new();
+        while self.eat_str("/memreserve/")? {
+            let address = self.parse_number()?;
+            let size = self.parse_number()?;
+            self.expect_char(';')?;
+            memory_reservations.push(MemoryReservation::new(address, size));
+        }
+
+        self.skip_trivia()?;
+        if !self.eat_char('/')? {
+            return Err(self.error(DtsErrorKind::MissingRoot));
+        }
+        let root = self.parse_node_body("/", String::new())?;
+
+        self.skip_trivia()?;
+        if !self.at_eof() {
+            return Err(self.error(DtsErrorKind::UnexpectedToken));
+        }
+
+        let mut tree = DeviceTree::new(root);
+        tree.memory_reservations = memory_reservations;
+        self.resolve_pending(&mut tree)?;
+        tree.rebuild_index();
+        Ok(tree)
+    }
+
+    fn parse_node_body(&mut self, own_path: &str, name: String) -> Result<DeviceTreeNode, DtsError> {
+        self.expect_char('{')?;
+        let mut node = DeviceTreeNode::new(name);
+        loop {
+            self.skip_trivia()?;
+            if self.eat_char('}')? {
+                self.expect_char(';')?;
+                break;
+            }
+            if self.at_eof() {
+                return Err(self.error(DtsErrorKind::UnexpectedEof));
+            }
+
+            if self.eat_str("/delete-node/")? {
+                match self.parse_delete_target()? {
+                    DeleteTarget::Name(child_name) => {
+                        node.remove_child(&child_name);
+                    }
+                    DeleteTarget::Label(label) => self.pending_deletes.push(PendingDelete { label }),
+                }
+                self.expect_char(';')?;
+                continue;
+            }
+            if self.eat_str("/delete-property/")? {
+                let prop_name = self.parse_name()?.to_string();
+                self.expect_char(';')?;
+                node.remove_property(&prop_name);
+                continue;
+            }
+
+            let (label, item_name) = self.parse_optional_label_and_name()?;
+            self.skip_trivia()?;
+            if self.peek() == Some('{') {
+                let child_path = join_path(own_path, item_name);
+                if let Some(label) = label {
+                    self.labels.insert(label.to_string(), child_path.clone());
+                }
+                let child = self.parse_node_body(&child_path, item_name.to_string())?;
+                node.add_child(child);
+            } else {
+                let property = self.parse_property_value(own_path, item_name)?;
+                node.add_property(property);
+            }
+        }
+        Ok(node)
+    }
+
+    fn parse_property_value(
+        &mut self,
+        node_path: &str,
+        name: &str,
+    ) -> Result<DeviceTreeProperty, DtsError> {
+        self.skip_trivia()?;
+        if self.eat_char(';')? {
+            return Ok(DeviceTreeProperty::new(name, Vec::new()));
+        }
+        self.expect_char('=')?;
+
+        let mut value = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            match self.peek() {
+                Some('"') => {
+                    let mut s = self.parse_string_literal()?;
+                    loop {
+                        self.skip_trivia()?;
+                        if self.peek() == Some('"') {
+                            s.push_str(&self.parse_string_literal()?);
+                        } else {
+                            break;
+                        }
+                    }
+                    value.extend_from_slice(s.as_bytes());
+                    value.push(0);
+                }
+                Some('<') => {
+                    self.bump();
+                    loop {
+                        self.skip_trivia()?;
+                        if self.eat_char('>')? {
+                            break;
+                        }
+                        if self.eat_char('&')? {
+                            let label = self.parse_ref_label()?;
+                            let cell_offset = value.len();
+                            value.extend_from_slice(&0u32.to_be_bytes());
+                            self.pending_refs.push(PendingRef {
+                                node_path: node_path.to_string(),
+                                prop_name: name.to_string(),
+                                cell_offset,
+                                label,
+                            });
+                        } else {
+                            let cell = self.parse_number()?;
+                            value.extend_from_slice(&(cell as u32).to_be_bytes());
+                        }
+                    }
+                }
+                Some('[') => {
+                    value.extend_from_slice(&self.parse_byte_string()?);
+                }
+                _ => return Err(self.error(DtsErrorKind::UnexpectedToken)),
+            }
+
+            self.skip_trivia()?;
+            if self.eat_char(',')? {
+                continue;
+            }
+            break;
+        }
+
+        self.expect_char(';')?;
+        Ok(DeviceTreeProperty::new(name, value))
+    }
+
+    fn parse_ref_label(&mut self) -> Result<String, DtsError> {
+        self.skip_trivia()?;
+        if self.eat_char('{')? {
+            let start = self.pos;
+            loop {
+                match self.peek() {
+                    Some('}') => break,
+                    Some(_) => {
+                        self.bump();
+                    }
+                    None => return Err(self.error(DtsErrorKind::UnexpectedEof)),
+                }
+            }
+            let path = self.src[start..self.pos].to_string();
+            self.bump();
+            Ok(alloc::format!("path:{path}"))
+        } else {
+            Ok(self.parse_name()?.to_string())
+        }
+    }
+
+    fn parse_delete_target(&mut self) -> Result<DeleteTarget, DtsError> {
+        self.skip_trivia()?;
+        if self.eat_char('&')? {
+            Ok(DeleteTarget::Label(self.parse_name()?.to_string()))
+        } else {
+            Ok(DeleteTarget::Name(self.parse_name()?.to_string()))
+        }
+    }
+
+    /// Applies every deferred `/delete-node/ &label;` directive and patches
+    /// every deferred `&label` phandle cell, now that every node's path is
+    /// known.
+    fn resolve_pending(&mut self, tree: &mut DeviceTree) -> Result<(), DtsError> {
+        for delete in core::mem::take(&mut self.pending_deletes) {
+            let path = self.resolve_label(&delete.label)?;
+            if let Some((parent_path, child_name)) = split_parent(&path)
+                && let Some(parent) = tree.root_mut().node_at_path_mut(parent_path)
+            {
+                parent.remove_child(child_name);
+            }
+        }
+
+        for pending in core::mem::take(&mut self.pending_refs) {
+            let target_path = if let Some(path) = pending.label.strip_prefix("path:") {
+                path.to_string()
+            } else {
+                self.resolve_label(&pending.label)?
+            };
+            let phandle = phandle_for_path(tree, &target_path)
+                .ok_or_else(|| DtsError::new(DtsErrorKind::UnknownLabel(pending.label.clone()), 0))?;
+
+            let node = tree
+                .root_mut()
+                .node_at_path_mut(&pending.node_path)
+                .ok_or_else(|| DtsError::new(DtsErrorKind::UnknownLabel(pending.label.clone()), 0))?;
+            let prop = node
+                .property_mut(&pending.prop_name)
+                .ok_or_else(|| DtsError::new(DtsErrorKind::UnknownLabel(pending.label.clone()), 0))?;
+            let mut value = prop.value().to_vec();
+            value[pending.cell_offset..pending.cell_offset + 4].copy_from_slice(&phandle.to_be_bytes());
+            prop.set_value(value);
+        }
+
+        Ok(())
+    }
+
+    fn resolve_label(&self, label: &str) -> Result<String, DtsError> {
+        self.labels
+            .get(label)
+            .cloned()
+            .ok_or_else(|| DtsError::new(DtsErrorKind::UnknownLabel(label.to_string()), 0))
+    }
+
+    fn remaining(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn at_eof(&self) -> bool {
+        self.pos >= self.src.len()
+    }
+
+    fn error(&self, kind: DtsErrorKind) -> DtsError {
+        DtsError::new(kind, self.pos)
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), DtsError> {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('/') if self.remaining().starts_with("//") => {
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        self.bump();
+                    }
+                }
+                Some('/') if self.remaining().starts_with("/*") => {
+                    let start = self.pos;
+                    self.pos += 2;
+                    match self.remaining().find("*/") {
+                        Some(idx) => self.pos += idx + 2,
+                        None => return Err(DtsError::new(DtsErrorKind::UnterminatedComment, start)),
+                    }
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn eat_char(&mut self, expected: char) -> Result<bool, DtsError> {
+        self.skip_trivia()?;
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect_char(&mut self, expected: char) -> Result<(), DtsError> {
+        if self.eat_char(expected)? {
+            Ok(())
+        } else {
+            Err(self.error(DtsErrorKind::UnexpectedToken))
+        }
+    }
+
+    fn eat_str(&mut self, keyword: &str) -> Result<bool, DtsError> {
+        self.skip_trivia()?;
+        if self.remaining().starts_with(keyword) {
+            self.pos += keyword.len();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Parses a run of node/property name characters (letters, digits, and
+    /// `,._+-?#@`).
+    fn parse_name(&mut self) -> Result<&'a str, DtsError> {
+        self.skip_trivia()?;
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || matches!(c, ',' | '.' | '_' | '+' | '-' | '?' | '#' | '@') {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if self.pos == start {
+            return Err(self.error(DtsErrorKind::UnexpectedToken));
+        }
+        Ok(&self.src[start..self.pos])
+    }
+
+    /// Parses an optional `label:` prefix followed by a name, returning
+    /// `(label, name)`.
+    fn parse_optional_label_and_name(&mut self) -> Result<(Option<&'a str>, &'a str), DtsError> {
+        let first = self.parse_name()?;
+        self.skip_trivia()?;
+        if self.peek() == Some(':') {
+            self.bump();
+            let name = self.parse_name()?;
+            Ok((Some(first), name))
+        } else {
+            Ok((None, first))
+        }
+    }
+
+    /// Parses a decimal or `0x`-prefixed hexadecimal integer literal,
+    /// skipping any trailing `u`/`l` suffix characters.
+    fn parse_number(&mut self) -> Result<u64, DtsError> {
+        self.skip_trivia()?;
+        let start = self.pos;
+        let hex = self.remaining().starts_with("0x") || self.remaining().starts_with("0X");
+        if hex {
+            self.pos += 2;
+        }
+        let digits_start = self.pos;
+        while let Some(c) = self.peek() {
+            let is_digit = if hex { c.is_ascii_hexdigit() } else { c.is_ascii_digit() };
+            if is_digit {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if self.pos == digits_start {
+            return Err(DtsError::new(DtsErrorKind::InvalidNumber, start));
+        }
+        let digits = &self.src[digits_start..self.pos];
+        let value = if hex {
+            u64::from_str_radix(digits, 16)
+        } else {
+            digits.parse::<u64>()
+        }
+        .map_err(|_err| DtsError::new(DtsErrorKind::InvalidNumber, start))?;
+        while matches!(self.peek(), Some('u' | 'U' | 'l' | 'L')) {
+            self.bump();
+        }
+        Ok(value)
+    }
+
+    /// Parses a `"..."` string literal, honoring `\n`/`\t`/`\r`/`\0`/`\\`/`\"`
+    /// escapes (any other escaped character is taken literally).
+    fn parse_string_literal(&mut self) -> Result<String, DtsError> {
+        let start = self.pos;
+        self.expect_char('"')?;
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(DtsError::new(DtsErrorKind::UnterminatedString, start)),
+                Some('"') => break,
+                Some('\\') => match self.bump() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('0') => s.push('\0'),
+                    Some(c) => s.push(c),
+                    None => return Err(DtsError::new(DtsErrorKind::UnterminatedString, start)),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+        Ok(s)
+    }
+
+    /// Parses a `[ 00 11 ff ]` byte string literal.
+    fn parse_byte_string(&mut self) -> Result<Vec<u8>, DtsError> {
+        self.expect_char('[')?;
+        let mut bytes = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.eat_char(']')? {
+                break;
+            }
+            let start = self.pos;
+            let mut hex = String::new();
+            for _ in 0..2 {
+                match self.peek() {
+                    Some(c) if c.is_ascii_hexdigit() => {
+                        hex.push(c);
+                        self.bump();
+                    }
+                    _ => return Err(DtsError::new(DtsErrorKind::InvalidByteString, start)),
+                }
+            }
+            let byte = u8::from_str_radix(&hex, 16)
+                .map_err(|_err| DtsError::new(DtsErrorKind::InvalidByteString, start))?;
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Joins a node's own path with a child's name, avoiding a doubled `/` when
+/// `parent` is the root.
+fn join_path(parent: &str, name: &str) -> String {
+    if parent == "/" {
+        alloc::format!("/{name}")
+    } else {
+        alloc::format!("{parent}/{name}")
+    }
+}
+
+/// Splits an absolute path into its parent path and final component, e.g.
+/// `/soc/uart` into (`/soc`, `uart`). Returns `None` for the root itself,
+/// which has no parent.
+fn split_parent(path: &str) -> Option<(&str, &str)> {
+    let trimmed = path.trim_end_matches('/');
+    let slash = trimmed.rfind('/')?;
+    let name = &trimmed[slash + 1..];
+    let parent = match &trimmed[..slash] {
+        "" => "/",
+        parent => parent,
+    };
+    Some((parent, name))
+}
+
+/// Returns the `phandle` of the node at `path`, allocating and assigning one
+/// via [`DeviceTree::allocate_phandle`] if it doesn't already have one.
+fn phandle_for_path(tree: &mut DeviceTree, path: &str) -> Option<u32> {
+    let next_phandle = tree.allocate_phandle();
+    let node = tree.root_mut().node_at_path_mut(path)?;
+    if let Some(phandle) = node.property("phandle").and_then(|prop| prop.as_u32().ok()) {
+        return Some(phandle);
+    }
+    node.add_property(DeviceTreeProperty::from_u32("phandle", next_phandle));
+    Some(next_phandle)
+}
@@ -13,17 +13,38 @@
 //! device tree in memory. The [`DeviceTree`] can then be serialized to a
 //! flattened device tree blob.
 
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt::Display;
 
-use crate::error::FdtError;
+use indexmap::IndexMap;
+use twox_hash::xxhash64;
+
+use crate::error::{FdtError, FdtErrorKind};
 use crate::fdt::Fdt;
 use crate::{MemoryReservation, writer};
+mod diff;
+mod dts;
 mod node;
 mod overlay;
 mod property;
+mod psci;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod topology;
+mod validate;
+pub use diff::{NodeDiff, TreeDiff};
+pub use dts::{DtsError, DtsErrorKind};
 pub use node::{DeviceTreeNode, DeviceTreeNodeBuilder};
-pub use property::DeviceTreeProperty;
+pub use overlay::{OverlayError, OverlayErrorKind};
+pub use property::{DeviceTreeProperty, PropertyValue};
+pub use psci::{PsciMethod, PsciVersion};
+pub use validate::{Severity, ValidationConstraints, ValidationError, ValidationErrorKind};
+
+/// The seed used for every [`xxhash64`]-backed map in this module, matching
+/// the one [`DeviceTreeNode`] uses for its own property/child maps.
+const HASH_SEED: u64 = 0xdead_cafe;
 
 /// A mutable, in-memory representation of a device tree.
 ///
@@ -39,13 +60,26 @@ pub use property::DeviceTreeProperty;
 /// tree.root_mut().add_child(DeviceTreeNode::new("child"));
 /// let child = tree.find_node_mut("/child").unwrap();
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct DeviceTree {
     pub(self) root: DeviceTreeNode,
     /// The memory reservations for this device tree.
     pub memory_reservations: Vec<MemoryReservation>,
+    /// Maps each `phandle`/`linux,phandle` value in the tree to the path of
+    /// the node that declares it.
+    phandle_index: IndexMap<u32, String, xxhash64::State>,
+    /// Maps each `/aliases` property name to the path it names.
+    alias_index: IndexMap<String, String, xxhash64::State>,
+}
+
+impl PartialEq for DeviceTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root && self.memory_reservations == other.memory_reservations
+    }
 }
 
+impl Eq for DeviceTree {}
+
 impl DeviceTree {
     /// Creates a new `DeviceTree` with the given root node.
     ///
@@ -58,10 +92,14 @@ impl DeviceTree {
     /// ```
     #[must_use]
     pub fn new(root: DeviceTreeNode) -> Self {
-        Self {
+        let mut tree = Self {
             root,
             memory_reservations: Vec::new(),
-        }
+            phandle_index: IndexMap::with_hasher(xxhash64::State::with_seed(HASH_SEED)),
+            alias_index: IndexMap::with_hasher(xxhash64::State::with_seed(HASH_SEED)),
+        };
+        tree.rebuild_index();
+        tree
     }
 
     /// Creates a new `DeviceTree` from a `Fdt`.
@@ -80,10 +118,14 @@ impl DeviceTree {
     /// Returns an error if the root node of the `Fdt` cannot be parsed.
     pub fn from_fdt(fdt: &Fdt<'_>) -> Result<Self, FdtError> {
         let root = DeviceTreeNode::try_from(fdt.root()?)?;
-        Ok(DeviceTree {
+        let mut tree = DeviceTree {
             root,
             memory_reservations: fdt.memory_reservations().collect(),
-        })
+            phandle_index: IndexMap::with_hasher(xxhash64::State::with_seed(HASH_SEED)),
+            alias_index: IndexMap::with_hasher(xxhash64::State::with_seed(HASH_SEED)),
+        };
+        tree.rebuild_index();
+        Ok(tree)
     }
 
     /// Serializes the `DeviceTree` to a flattened device tree blob.
@@ -108,8 +150,29 @@ impl DeviceTree {
         &mut self.root
     }
 
+    /// Finds a node by its path and returns a reference to it.
+    ///
+    /// `path` may start with an alias name (a property of the `/aliases`
+    /// node) instead of a leading `/`, e.g. `"serial0/child"`, in which case
+    /// the alias is resolved via [`DeviceTree::resolve_alias`] first.
+    ///
+    /// # Performance
+    ///
+    /// This method traverses the device tree, but since child lookup is a
+    /// constant-time operation, performance is linear in the number of path
+    /// segments.
+    #[must_use]
+    pub fn find_node(&self, path: &str) -> Option<&DeviceTreeNode> {
+        let path = self.resolve_aliased_path(path)?;
+        self.root.node_at_path(&path)
+    }
+
     /// Finds a node by its path and returns a mutable reference to it.
     ///
+    /// `path` may start with an alias name (a property of the `/aliases`
+    /// node) instead of a leading `/`, e.g. `"serial0/child"`, in which case
+    /// the alias is resolved via [`DeviceTree::resolve_alias`] first.
+    ///
     /// # Performance
     ///
     /// This method traverses the device tree, but since child lookup is a
@@ -126,13 +189,11 @@ impl DeviceTree {
     /// assert_eq!(child.name(), "child");
     /// ```
     pub fn find_node_mut(&mut self, path: &str) -> Option<&mut DeviceTreeNode> {
-        if !path.starts_with('/') {
-            return None;
-        }
-        let mut current_node = &mut self.root;
+        let path = self.resolve_aliased_path(path)?;
         if path == "/" {
-            return Some(current_node);
+            return Some(&mut self.root);
         }
+        let mut current_node = &mut self.root;
         for component in path.split('/').filter(|s| !s.is_empty()) {
             match current_node.child_mut(component) {
                 Some(node) => current_node = node,
@@ -141,6 +202,419 @@ impl DeviceTree {
         }
         Some(current_node)
     }
+
+    /// Translates a bus address into a CPU physical address.
+    ///
+    /// `path` identifies the bus node whose child address space `address` is
+    /// expressed in (e.g. the parent of the node whose `reg` property
+    /// `address` came from). This walks up from `path` to the root, applying
+    /// each ancestor's `ranges` property in turn, so that MMIO addresses can
+    /// be resolved correctly across bus bridges (e.g. to set up stage-2
+    /// mappings for a passthrough device).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtErrorKind::BadPath`] if `path` does not start with `/`,
+    /// or [`FdtErrorKind::NotFound`] if it does not identify a node in the
+    /// tree. See [`DeviceTreeNode::translate_through_ranges`] for errors
+    /// translation itself can return.
+    pub fn translate_address(&self, path: &str, address: u64) -> Result<u64, FdtError> {
+        if !path.starts_with('/') {
+            return Err(FdtError::new(FdtErrorKind::BadPath, 0));
+        }
+        if path == "/" {
+            return Ok(address);
+        }
+
+        let mut chain = Vec::new();
+        let mut node = &self.root;
+        for component in path.split('/').filter(|s| !s.is_empty()) {
+            node = node
+                .child(component)
+                .ok_or_else(|| FdtError::new(FdtErrorKind::NotFound, 0))?;
+            chain.push(node);
+        }
+
+        let mut translated = address;
+        for pair in chain.windows(2).rev() {
+            translated = pair[1].translate_through_ranges(pair[0], translated)?;
+        }
+        if let Some(top) = chain.first() {
+            translated = top.translate_through_ranges(&self.root, translated)?;
+        }
+        Ok(translated)
+    }
+
+    /// Returns the node whose `phandle`/`linux,phandle` property equals
+    /// `phandle`, using the index built by [`DeviceTree::rebuild_index`].
+    ///
+    /// # Performance
+    ///
+    /// This is a constant-time lookup, backed by a fast non-cryptographic
+    /// hash, rather than a walk of the tree.
+    #[must_use]
+    pub fn node_by_phandle(&self, phandle: u32) -> Option<&DeviceTreeNode> {
+        let path = self.phandle_index.get(&phandle)?;
+        self.root.node_at_path(path)
+    }
+
+    /// Resolves an alias name (a property of the `/aliases` node) to the
+    /// absolute path it names, using the index built by
+    /// [`DeviceTree::rebuild_index`].
+    #[must_use]
+    pub fn resolve_alias(&self, alias: &str) -> Option<&str> {
+        self.alias_index.get(alias).map(String::as_str)
+    }
+
+    /// Resolves a label (a property of a `/__symbols__` node, as `dtc -@`
+    /// emits for every labeled node) to the node it names.
+    #[must_use]
+    pub fn resolve_symbol(&self, label: &str) -> Option<&DeviceTreeNode> {
+        let path = self
+            .root
+            .child("__symbols__")
+            .and_then(|symbols| symbols.property(label))
+            .and_then(|prop| prop.as_string().ok())?;
+        self.root.node_at_path(path)
+    }
+
+    /// Ensures every node listed in `/__symbols__` has a `phandle`,
+    /// assigning fresh monotonically-increasing values (starting just past
+    /// the tree's current maximum) to any that don't, then rebuilds
+    /// `/__symbols__` itself, dropping entries whose target no longer
+    /// exists.
+    ///
+    /// This is the counterpart to the resolver logic `apply_overlay` already
+    /// performs against an existing base tree: it lets callers building a
+    /// tree from scratch label nodes first and only pay for phandle
+    /// allocation once, right before serializing, rather than assigning one
+    /// up front to every node that might end up referenced.
+    pub fn assign_phandles(&mut self) {
+        let Some(symbols) = self.root.child("__symbols__") else {
+            return;
+        };
+        let labels: Vec<(String, String)> = symbols
+            .properties()
+            .filter_map(|prop| Some((prop.name().to_owned(), prop.as_string().ok()?.to_owned())))
+            .collect();
+
+        let mut next_phandle = self.root.max_phandle() + 1;
+        let mut resolved = Vec::new();
+        for (label, path) in labels {
+            let Some(node) = self.root.node_at_path_mut(&path) else {
+                continue;
+            };
+            if node.phandle().is_none() {
+                node.add_property(DeviceTreeProperty::from_u32("phandle", next_phandle));
+                next_phandle += 1;
+            }
+            resolved.push((label, path));
+        }
+
+        self.root.remove_child("__symbols__");
+        let mut symbols = DeviceTreeNode::new("__symbols__");
+        for (label, path) in resolved {
+            symbols.add_property(DeviceTreeProperty::from_string(label, path));
+        }
+        self.root.add_child(symbols);
+
+        self.rebuild_index();
+    }
+
+    /// Rebuilds the indices backing [`DeviceTree::node_by_phandle`] and
+    /// [`DeviceTree::resolve_alias`] from the tree's current contents.
+    ///
+    /// This is called automatically by [`DeviceTree::new`] and
+    /// [`DeviceTree::from_fdt`]; call it again after mutating the tree (e.g.
+    /// via [`DeviceTree::root_mut`] or [`DeviceTree::apply_overlay`]) if you
+    /// rely on either lookup reflecting the change, since the indices are
+    /// otherwise a snapshot taken at construction time.
+    pub fn rebuild_index(&mut self) {
+        self.phandle_index.clear();
+        self.alias_index.clear();
+
+        if let Some(phandle) = self.root.phandle() {
+            self.phandle_index.insert(phandle, "/".to_string());
+        }
+        for (path, node) in self.root.descendants() {
+            if let Some(phandle) = node.phandle() {
+                self.phandle_index.insert(phandle, path);
+            }
+        }
+
+        if let Some(aliases) = self.root.child("aliases") {
+            for prop in aliases.properties() {
+                if let Ok(target) = prop.as_string() {
+                    self.alias_index
+                        .insert(prop.name().to_owned(), target.to_owned());
+                }
+            }
+        }
+    }
+
+    /// Returns a phandle value not used anywhere in this tree, suitable for
+    /// assigning to a freshly created node.
+    ///
+    /// This does not reserve the returned value; assign it to the node's
+    /// `phandle` property before calling this again, or it may be returned
+    /// a second time.
+    ///
+    /// # Performance
+    ///
+    /// This walks the whole tree each time it is called, rather than relying
+    /// on [`DeviceTree::rebuild_index`], since a subtree imported after the
+    /// indices were last built (e.g. via [`DeviceTree::apply_overlay`]) may
+    /// use phandles the index doesn't know about yet.
+    #[must_use]
+    pub fn allocate_phandle(&self) -> u32 {
+        self.root.max_phandle() + 1
+    }
+
+    /// Returns a mutable reference to this tree's `/chosen` node, creating it
+    /// if it doesn't already exist.
+    pub fn chosen_mut(&mut self) -> &mut DeviceTreeNode {
+        if self.root.child("chosen").is_none() {
+            self.root.add_child(DeviceTreeNode::new("chosen"));
+        }
+        self.root
+            .child_mut("chosen")
+            .expect("just inserted above")
+    }
+
+    /// Returns a reference to this tree's `/chosen` node, if it has one.
+    #[must_use]
+    pub fn chosen(&self) -> Option<&DeviceTreeNode> {
+        self.root.child("chosen")
+    }
+
+    /// Returns the kernel command line the guest should use, from
+    /// `/chosen/bootargs`.
+    #[must_use]
+    pub fn bootargs(&self) -> Option<&str> {
+        self.chosen()?.property("bootargs")?.as_string().ok()
+    }
+
+    /// Returns the device the guest kernel should use for console output,
+    /// from `/chosen/stdout-path`.
+    #[must_use]
+    pub fn stdout_path(&self) -> Option<&str> {
+        self.chosen()?.property("stdout-path")?.as_string().ok()
+    }
+
+    /// Returns the device the guest kernel should use for console input,
+    /// from `/chosen/stdin-path`.
+    #[must_use]
+    pub fn stdin_path(&self) -> Option<&str> {
+        self.chosen()?.property("stdin-path")?.as_string().ok()
+    }
+
+    /// Sets the kernel command line the guest should use, via
+    /// `/chosen/bootargs`.
+    pub fn set_bootargs(&mut self, bootargs: impl AsRef<str>) {
+        self.chosen_mut()
+            .add_property(DeviceTreeProperty::from_string("bootargs", bootargs));
+    }
+
+    /// Sets the guest-physical address range of an initial ramdisk, via
+    /// `/chosen/linux,initrd-start` and `/chosen/linux,initrd-end`.
+    pub fn set_initrd(&mut self, start: u64, end: u64) {
+        let chosen = self.chosen_mut();
+        chosen.add_property(DeviceTreeProperty::from_u64("linux,initrd-start", start));
+        chosen.add_property(DeviceTreeProperty::from_u64("linux,initrd-end", end));
+    }
+
+    /// Records a `(address, size)` memory reservation, serialized into the
+    /// blob's `mem_rsvmap` section by [`DeviceTree::to_dtb`].
+    ///
+    /// Unlike [`DeviceTree::add_reserved_memory_region`], this does not add a
+    /// node to the tree itself: `mem_rsvmap` entries are opaque to anything
+    /// walking the tree (the firmware that hands the kernel its DTB reads
+    /// them before the tree is even unflattened), so use this for regions
+    /// only the earliest boot code needs to avoid, e.g. the DTB's own
+    /// backing memory.
+    pub fn add_reserved_region(&mut self, address: u64, size: u64) {
+        self.memory_reservations.push(MemoryReservation::new(address, size));
+    }
+
+    /// Returns a mutable reference to this tree's `/reserved-memory` node,
+    /// creating it (with `#address-cells`/`#size-cells` matching the root's)
+    /// if it doesn't already exist.
+    pub fn reserved_memory_mut(&mut self) -> &mut DeviceTreeNode {
+        if self.root.child("reserved-memory").is_none() {
+            let address_cells = self.root.address_cells().unwrap_or(2);
+            let size_cells = self.root.size_cells().unwrap_or(1);
+            let mut reserved_memory = DeviceTreeNode::new("reserved-memory");
+            reserved_memory.add_property(DeviceTreeProperty::from_u32("#address-cells", address_cells));
+            reserved_memory.add_property(DeviceTreeProperty::from_u32("#size-cells", size_cells));
+            reserved_memory.add_property(DeviceTreeProperty::new("ranges", Vec::new()));
+            self.root.add_child(reserved_memory);
+        }
+        self.root
+            .child_mut("reserved-memory")
+            .expect("just inserted above")
+    }
+
+    /// Adds a `name@address` child of `/reserved-memory` describing a region
+    /// the OS must not use for general allocation, e.g. a crashkernel area
+    /// or firmware-owned buffer, the way PowerPC `prom.c` and barebox
+    /// `reserved-mem.c` carve theirs out.
+    ///
+    /// `no_map` sets the region's `no-map` property, telling the OS not to
+    /// create any (cacheable) mapping for it at all, rather than merely
+    /// excluding it from the general allocator.
+    ///
+    /// Unlike [`DeviceTree::add_reserved_region`], this is visible to the OS
+    /// as an ordinary node once it parses the tree, so drivers can look it
+    /// up (e.g. by phandle, via a `memory-region` reference) rather than
+    /// needing to know the address ahead of time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/reserved-memory`'s `#address-cells` or
+    /// `#size-cells` property is present but malformed, or greater than 2
+    /// (which wouldn't fit `address`/`size` in a `u64` cell pair).
+    pub fn add_reserved_memory_region(
+        &mut self,
+        name: impl AsRef<str>,
+        address: u64,
+        size: u64,
+        no_map: bool,
+    ) -> Result<(), FdtError> {
+        let reserved_memory = self.reserved_memory_mut();
+        let address_cells = reserved_memory.address_cells()?;
+        let size_cells = reserved_memory.size_cells()?;
+        if address_cells > 2 || size_cells > 2 {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        }
+
+        let mut reg = Vec::new();
+        push_cells(&mut reg, address, address_cells);
+        push_cells(&mut reg, size, size_cells);
+
+        let mut region = DeviceTreeNode::new(alloc::format!("{}@{:x}", name.as_ref(), address));
+        region.add_property(DeviceTreeProperty::new("reg", reg));
+        if no_map {
+            region.add_property(DeviceTreeProperty::new("no-map", Vec::new()));
+        }
+        reserved_memory.add_child(region);
+        Ok(())
+    }
+
+    /// Sets the device the guest kernel should use for its own console
+    /// output, via `/chosen/stdout-path`.
+    pub fn set_stdout_path(&mut self, path: impl AsRef<str>) {
+        self.chosen_mut()
+            .add_property(DeviceTreeProperty::from_string("stdout-path", path));
+    }
+
+    /// Adds a `/memory@<base>` node describing a contiguous region of guest
+    /// RAM, using the root node's `#address-cells`/`#size-cells`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root's `#address-cells` or `#size-cells`
+    /// property is present but malformed, or greater than 2 (which wouldn't
+    /// fit `base`/`size` in a `u64` cell pair).
+    pub fn add_memory(&mut self, base: u64, size: u64) -> Result<(), FdtError> {
+        let address_cells = self.root.address_cells()?;
+        let size_cells = self.root.size_cells()?;
+        if address_cells > 2 || size_cells > 2 {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        }
+
+        let mut reg = Vec::new();
+        push_cells(&mut reg, base, address_cells);
+        push_cells(&mut reg, size, size_cells);
+
+        let mut node = DeviceTreeNode::new(alloc::format!("memory@{base:x}"));
+        node.add_property(DeviceTreeProperty::from_string("device_type", "memory"));
+        node.add_property(DeviceTreeProperty::new("reg", reg));
+        self.root.add_child(node);
+        Ok(())
+    }
+
+    /// Rewrites the tree's memory node (the child whose `device_type`
+    /// property is `"memory"`, creating one named after `regions`' first
+    /// entry if none exists yet) so its `reg` property holds exactly
+    /// `regions`, packed as `(base, size)` cell pairs using the root node's
+    /// `#address-cells`/`#size-cells`.
+    ///
+    /// This is a full replacement, not a merge: call it once with the
+    /// guest's complete memory map, the way a bootloader patches a loaded
+    /// blob's memory node just before handing it to the kernel, rather than
+    /// calling it repeatedly to add banks (see [`DeviceTree::add_memory`]
+    /// for that instead).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `regions` is empty, or if the root's
+    /// `#address-cells` or `#size-cells` property is present but malformed,
+    /// or greater than 2 (which wouldn't fit a region's base/size in a `u64`
+    /// cell pair).
+    pub fn set_memory(&mut self, regions: &[(u64, u64)]) -> Result<(), FdtError> {
+        let Some(&(first_base, _)) = regions.first() else {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        };
+
+        let address_cells = self.root.address_cells()?;
+        let size_cells = self.root.size_cells()?;
+        if address_cells > 2 || size_cells > 2 {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        }
+
+        let mut reg = Vec::new();
+        for &(base, size) in regions {
+            push_cells(&mut reg, base, address_cells);
+            push_cells(&mut reg, size, size_cells);
+        }
+
+        let memory_path = self
+            .root
+            .children()
+            .find(|child| child.property("device_type").and_then(|prop| prop.as_string().ok()) == Some("memory"))
+            .map(|child| child.name().to_string());
+
+        let memory = match memory_path {
+            Some(name) => self.root.child_mut(&name).expect("just found above"),
+            None => {
+                self.root
+                    .add_child(DeviceTreeNode::new(alloc::format!("memory@{first_base:x}")));
+                self.root
+                    .child_mut(&alloc::format!("memory@{first_base:x}"))
+                    .expect("just inserted above")
+            }
+        };
+        memory.add_property(DeviceTreeProperty::from_string("device_type", "memory"));
+        memory.add_property(DeviceTreeProperty::new("reg", reg));
+        Ok(())
+    }
+
+    /// Resolves a leading alias in `path` (a non-`/`-prefixed first segment
+    /// matched against `/aliases`) to an absolute path; returns `path`
+    /// unchanged if it already starts with `/`.
+    fn resolve_aliased_path<'p>(&self, path: &'p str) -> Option<Cow<'p, str>> {
+        if path.starts_with('/') {
+            return Some(Cow::Borrowed(path));
+        }
+        let mut parts = path.splitn(2, '/');
+        let alias = parts.next()?;
+        let target = self.resolve_alias(alias)?;
+        Some(match parts.next() {
+            Some(rest) if !rest.is_empty() => Cow::Owned(alloc::format!("{target}/{rest}")),
+            _ => Cow::Owned(target.to_string()),
+        })
+    }
+}
+
+/// Appends `value` to `bytes` as `cells` consecutive big-endian `u32`s, most
+/// significant cell first. The inverse of the `fold_cells` helper in
+/// `property.rs`.
+fn push_cells(bytes: &mut Vec<u8>, value: u64, cells: u32) {
+    match cells {
+        1 => bytes.extend_from_slice(&(value as u32).to_be_bytes()),
+        2 => bytes.extend_from_slice(&value.to_be_bytes()),
+        _ => {}
+    }
 }
 
 impl Display for DeviceTree {
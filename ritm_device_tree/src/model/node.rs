@@ -7,7 +7,10 @@
 // except according to those terms.
 
 use super::property::DeviceTreeProperty;
-use crate::{error::Error, fdt::FdtNode};
+use crate::error::FdtError;
+use crate::error::FdtErrorKind;
+use crate::fdt::FdtNode;
+use crate::writer;
 use alloc::{
     borrow::ToOwned,
     string::{String, ToString},
@@ -254,10 +257,250 @@ impl DeviceTreeNode {
     pub fn remove_child(&mut self, name: &str) -> Option<DeviceTreeNode> {
         self.children.shift_remove(name)
     }
+
+    /// Finds a descendant node by its slash-separated path, relative to this
+    /// node.
+    ///
+    /// # Performance
+    ///
+    /// Since child lookup is a constant-time operation, this is linear in
+    /// the number of path segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeNode;
+    /// let mut root = DeviceTreeNode::new("/");
+    /// let mut soc = DeviceTreeNode::new("soc");
+    /// soc.add_child(DeviceTreeNode::new("serial@12340000"));
+    /// root.add_child(soc);
+    /// let serial = root.node_at_path("/soc/serial@12340000").unwrap();
+    /// assert_eq!(serial.name(), "serial@12340000");
+    /// ```
+    #[must_use]
+    pub fn node_at_path(&self, path: &str) -> Option<&DeviceTreeNode> {
+        let mut node = self;
+        for component in path.split('/').filter(|s| !s.is_empty()) {
+            node = node.child(component)?;
+        }
+        Some(node)
+    }
+
+    /// Finds a descendant node by its slash-separated path, relative to this
+    /// node, and returns a mutable reference to it.
+    ///
+    /// # Performance
+    ///
+    /// Since child lookup is a constant-time operation, this is linear in
+    /// the number of path segments.
+    pub fn node_at_path_mut(&mut self, path: &str) -> Option<&mut DeviceTreeNode> {
+        let mut node = self;
+        for component in path.split('/').filter(|s| !s.is_empty()) {
+            node = node.child_mut(component)?;
+        }
+        Some(node)
+    }
+
+    /// Returns an iterator over all descendants of this node (not including
+    /// this node itself), paired with their path relative to this node, in
+    /// depth-first order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeNode;
+    /// let mut root = DeviceTreeNode::new("/");
+    /// let mut soc = DeviceTreeNode::new("soc");
+    /// soc.add_child(DeviceTreeNode::new("serial@12340000"));
+    /// root.add_child(soc);
+    /// let paths: Vec<_> = root.descendants().map(|(path, _)| path).collect();
+    /// assert_eq!(paths, ["/soc", "/soc/serial@12340000"]);
+    /// ```
+    #[must_use]
+    pub fn descendants(&self) -> alloc::vec::IntoIter<(String, &DeviceTreeNode)> {
+        let mut descendants = Vec::new();
+        self.collect_descendants(String::new(), &mut descendants);
+        descendants.into_iter()
+    }
+
+    fn collect_descendants<'a>(&'a self, prefix: String, out: &mut Vec<(String, &'a DeviceTreeNode)>) {
+        for child in self.children() {
+            let path = alloc::format!("{prefix}/{}", child.name());
+            out.push((path.clone(), child));
+            child.collect_descendants(path, out);
+        }
+    }
+
+    /// Finds the descendant (or this node itself) whose `phandle` or
+    /// `linux,phandle` property equals `phandle`.
+    ///
+    /// # Performance
+    ///
+    /// This walks the subtree rooted at this node each time it is called,
+    /// since the tree may be mutated between calls.
+    #[must_use]
+    pub fn resolve_phandle(&self, phandle: u32) -> Option<&DeviceTreeNode> {
+        if self.phandle() == Some(phandle) {
+            return Some(self);
+        }
+        self.children().find_map(|child| child.resolve_phandle(phandle))
+    }
+
+    /// Returns this node's own `phandle`/`linux,phandle` value, if it has
+    /// one, without considering its descendants.
+    #[must_use]
+    pub fn phandle(&self) -> Option<u32> {
+        self.property("phandle")
+            .or_else(|| self.property("linux,phandle"))
+            .and_then(|prop| prop.as_u32().ok())
+    }
+
+    /// Finds the descendant (or this node itself) whose `phandle` or
+    /// `linux,phandle` property equals `phandle`, and returns a mutable
+    /// reference to it.
+    ///
+    /// # Performance
+    ///
+    /// This walks the subtree rooted at this node each time it is called,
+    /// since the tree may be mutated between calls.
+    pub fn resolve_phandle_mut(&mut self, phandle: u32) -> Option<&mut DeviceTreeNode> {
+        if self.phandle() == Some(phandle) {
+            return Some(self);
+        }
+        self.children_mut()
+            .find_map(|child| child.resolve_phandle_mut(phandle))
+    }
+
+    /// Returns this node's `#address-cells` value, defaulting to 2 if the
+    /// property is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the property is present but not a valid `u32`.
+    pub fn address_cells(&self) -> Result<u32, FdtError> {
+        match self.property("#address-cells") {
+            Some(prop) => prop.as_u32(),
+            None => Ok(2),
+        }
+    }
+
+    /// Returns this node's `#size-cells` value, defaulting to 1 if the
+    /// property is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the property is present but not a valid `u32`.
+    pub fn size_cells(&self) -> Result<u32, FdtError> {
+        match self.property("#size-cells") {
+            Some(prop) => prop.as_u32(),
+            None => Ok(1),
+        }
+    }
+
+    /// Translates `address`, expressed in this node's own child bus address
+    /// space, into `parent`'s address space using this node's `ranges`
+    /// property.
+    ///
+    /// An empty `ranges` property means this bus is a 1:1 pass-through, so
+    /// `address` is returned unchanged. If this node has no `ranges`
+    /// property at all, it is not memory-mapped onto `parent`, so
+    /// translation fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtErrorKind::NotMemoryMapped`] if this node has no
+    /// `ranges` property, or if `address` does not fall within any of the
+    /// windows a non-empty `ranges` property describes.
+    pub fn translate_through_ranges(
+        &self,
+        parent: &DeviceTreeNode,
+        address: u64,
+    ) -> Result<u64, FdtError> {
+        let Some(prop) = self.property("ranges") else {
+            return Err(FdtError::new(FdtErrorKind::NotMemoryMapped, 0));
+        };
+        if prop.value().is_empty() {
+            return Ok(address);
+        }
+
+        let child_address_cells = self.address_cells()?;
+        let parent_address_cells = parent.address_cells()?;
+        let size_cells = self.size_cells()?;
+        for (child_bus_address, parent_bus_address, length) in
+            prop.as_ranges(child_address_cells, parent_address_cells, size_cells)?
+        {
+            if let Some(offset) = address.checked_sub(child_bus_address)
+                && offset < length
+            {
+                return Ok(parent_bus_address + offset);
+            }
+        }
+        Err(FdtError::new(FdtErrorKind::NotMemoryMapped, 0))
+    }
+
+    /// Returns the largest `phandle`/`linux,phandle` value used anywhere in
+    /// this node's subtree, or `0` if none is set.
+    #[must_use]
+    pub fn max_phandle(&self) -> u32 {
+        self.children()
+            .map(DeviceTreeNode::max_phandle)
+            .fold(self.phandle().unwrap_or(0), u32::max)
+    }
+
+    /// Returns the path from this node to `target`, relative to this node,
+    /// if `target` is this node or one of its descendants.
+    ///
+    /// Nodes are compared by identity rather than by name or value, since
+    /// sibling nodes may share a name.
+    #[must_use]
+    pub fn path_of(&self, target: &DeviceTreeNode) -> Option<String> {
+        if core::ptr::eq(self, target) {
+            return Some(String::new());
+        }
+        for child in self.children() {
+            if let Some(rest) = child.path_of(target) {
+                return Some(alloc::format!("/{}{rest}", child.name()));
+            }
+        }
+        None
+    }
+
+    /// Serializes this node into a flattened device tree blob, with an empty
+    /// memory reservation block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::fdt::Fdt;
+    /// # use ritm_device_tree::model::DeviceTreeNode;
+    /// let node = DeviceTreeNode::new("");
+    /// let dtb = node.to_dtb();
+    /// assert!(Fdt::new(&dtb).is_ok());
+    /// ```
+    #[must_use]
+    pub fn to_dtb(&self) -> Vec<u8> {
+        writer::to_bytes_from_parts(self, &[])
+    }
+
+    /// Serializes this node into `buf`, returning the number of bytes
+    /// written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtErrorKind::NoSpace`] if `buf` is smaller than the
+    /// serialized blob.
+    pub fn write_dtb(&self, buf: &mut [u8]) -> crate::Result<usize> {
+        let dtb = self.to_dtb();
+        if buf.len() < dtb.len() {
+            return Err(FdtError::new(FdtErrorKind::NoSpace, buf.len()));
+        }
+        buf[..dtb.len()].copy_from_slice(&dtb);
+        Ok(dtb.len())
+    }
 }
 
 impl<'a> TryFrom<FdtNode<'a>> for DeviceTreeNode {
-    type Error = Error;
+    type Error = FdtError;
 
     fn try_from(node: FdtNode<'a>) -> Result<Self, Self::Error> {
         let name = node.name()?.to_string();
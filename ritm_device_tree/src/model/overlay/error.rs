@@ -31,12 +31,24 @@ impl OverlayError {
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum OverlayErrorKind {
+    /// A fragment has neither a `target` nor a `target-path` property.
     TargetPathNotFound,
+    /// A fragment's `target-path` property is not a valid string.
     TargetPathInvalid,
+    /// A fragment's target (by path or by phandle) does not exist in the
+    /// base tree.
     TargetNodeNotFound,
+    /// A fragment is missing its `__overlay__` child.
     SourceNodeNotFound,
+    /// A node referenced by `__local_fixups__` or `__fixups__` is missing
+    /// the property the fixup is meant to patch.
     PhandleNotFound,
+    /// A `phandle`/`linux,phandle` property, or a fixup offset list, could
+    /// not be decoded.
     CorruptedPhandle,
+    /// A `__fixups__` entry names a label with no corresponding entry in the
+    /// base tree's `__symbols__` node.
+    SymbolNotFound,
 }
 
 impl fmt::Display for OverlayError {
@@ -54,6 +66,7 @@ impl fmt::Display for OverlayErrorKind {
             Self::SourceNodeNotFound => write!(f, "source node not found"),
             Self::PhandleNotFound => write!(f, "phandle property not found"),
             Self::CorruptedPhandle => write!(f, "phandle property is corrupted"),
+            Self::SymbolNotFound => write!(f, "label not found in the base tree's __symbols__"),
         }
     }
 }
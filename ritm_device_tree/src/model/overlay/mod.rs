@@ -7,182 +7,343 @@
 // except according to those terms.
 
 use alloc::borrow::ToOwned;
-use alloc::vec::Vec;
+use alloc::string::ToString;
 
-use crate::model::overlay::error::{OverlayError, OverlayErrorKind};
-use crate::model::{DeviceTree, DeviceTreeNode};
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
 
 mod error;
+pub use error::{OverlayError, OverlayErrorKind};
 
 impl DeviceTree {
-    /// Applies a device tree overlay to this device tree.
+    /// Applies a devicetree overlay onto this device tree's root, mirroring
+    /// what `of_overlay_apply` does for a kernel already running (here,
+    /// entirely in memory, so it can be called any number of times before
+    /// the first [`DeviceTree::to_dtb`]): each `fragment@N` node's
+    /// `__overlay__` content is merged into its resolved target, with
+    /// overlay-local phandles renumbered to avoid colliding with this tree's.
+    ///
+    /// `overlay` is expected to carry the metadata `dtc -@` emits alongside
+    /// each fragment: a `__fixups__` node recording where the overlay
+    /// references this tree's labels (resolved to paths through this tree's
+    /// own `__symbols__` node), and a `__local_fixups__` node recording where it
+    /// references its own, overlay-local phandles (which are renumbered to
+    /// avoid colliding with this tree's existing ones before being spliced
+    /// in). See [`DeviceTreeNode::apply_overlay`] for the full algorithm.
+    ///
+    /// # Errors
+    ///
+    /// See [`DeviceTreeNode::apply_overlay`].
+    ///
+    /// # Examples
+    ///
+    /// Merging a base and overlay FDT into a single blob, since `Fdt` itself
+    /// is read-only and has no merge capability of its own:
+    ///
+    /// ```
+    /// # use ritm_device_tree::{fdt::Fdt, model::DeviceTree};
+    /// # let base_dtb = include_bytes!("../../dtb/test_overlay_base.dtb");
+    /// # let overlay_dtb = include_bytes!("../../dtb/test_overlay.dtb");
+    /// let mut base = DeviceTree::from_fdt(&Fdt::new(base_dtb).unwrap()).unwrap();
+    /// let overlay = DeviceTree::from_fdt(&Fdt::new(overlay_dtb).unwrap()).unwrap();
+    /// base.apply_overlay(&overlay).unwrap();
+    /// let merged_dtb = base.to_dtb();
+    /// ```
+    pub fn apply_overlay(&mut self, overlay: &DeviceTree) -> Result<(), OverlayError> {
+        self.root.apply_overlay(overlay.root())
+    }
+}
+
+impl DeviceTreeNode {
+    /// Applies a devicetree overlay onto this node.
+    ///
+    /// `overlay`'s root is expected to hold the standard overlay layout: one
+    /// `fragment@N` child per patch, each with either a `target` property
+    /// (a phandle into this tree) or a `target-path` property (a string
+    /// path), and an `__overlay__` child whose properties and children are
+    /// merged into the resolved node (properties overwrite, child subtrees
+    /// recurse). Phandles the overlay defines are renumbered to stay unique
+    /// against this tree, using `__local_fixups__` to patch references
+    /// internal to the overlay and `__fixups__` (resolved through this
+    /// tree's `__symbols__`) to patch references to existing labels,
+    /// including a fragment's own `target` when it names a base label
+    /// (`dtc` emits this as a fixup rather than a literal phandle, since the
+    /// label isn't resolvable until the overlay is applied). If a
+    /// referenced base node has no `phandle` yet, one is allocated and
+    /// added to it.
+    ///
+    /// Merging happens fragment by fragment; if a fragment fails to resolve,
+    /// fragments already merged are not rolled back.
     ///
     /// # Errors
     ///
-    /// Returns an error if the overlay is malformed, e.g. a fragment is missing
-    /// a `target-path` or `__overlay__` node.
-    pub fn apply_overlay(&mut self, overlay: DeviceTree) -> Result<(), OverlayError> {
-        let overlay_root = overlay.root;
-        let mut phandle_map = PhandleMap::new(self)?;
-
-        for fragment in overlay_root.children.into_values() {
-            let target_path = fragment
-                .property("target-path")
+    /// Returns an error if the overlay is malformed, e.g. a fragment is
+    /// missing a target or an `__overlay__` node, a fixup's offset lands
+    /// outside its property's value, or a `__fixups__` label has no
+    /// corresponding `__symbols__` entry in this tree.
+    pub fn apply_overlay(&mut self, overlay: &DeviceTreeNode) -> Result<(), OverlayError> {
+        let phandle_offset = self.max_phandle();
+        let overlay_max_phandle = overlay
+            .children()
+            .filter(|fragment| !is_special(fragment.name()))
+            .filter_map(|fragment| fragment.child("__overlay__"))
+            .map(DeviceTreeNode::max_phandle)
+            .max()
+            .unwrap_or(0);
+        let mut next_auto_phandle = phandle_offset + overlay_max_phandle + 1;
+
+        let local_fixups = overlay.child("__local_fixups__");
+        let fixups = overlay.child("__fixups__");
+
+        for fragment in overlay.children() {
+            if is_special(fragment.name()) {
+                continue;
+            }
+
+            let mut content = fragment
+                .child("__overlay__")
                 .ok_or_else(|| {
-                    OverlayError::new(
-                        OverlayErrorKind::TargetPathNotFound,
-                        fragment.name().to_owned(),
-                    )
+                    OverlayError::new(OverlayErrorKind::SourceNodeNotFound, fragment.name().to_owned())
                 })?
-                .as_str()
-                .map_err(|_err| {
-                    OverlayError::new(
-                        OverlayErrorKind::TargetPathInvalid,
-                        fragment.name().to_owned(),
-                    )
-                })?;
-            let target_node = self.find_node_mut(target_path).ok_or_else(|| {
-                OverlayError::new(
-                    OverlayErrorKind::TargetNodeNotFound,
-                    fragment.name().to_owned(),
-                )
-            })?;
-
-            let overlay_node = fragment
-                .children
-                .clone()
-                .into_values()
-                .find(|c| c.name() == "__overlay__")
-                .ok_or_else(|| {
-                    OverlayError::new(
-                        OverlayErrorKind::SourceNodeNotFound,
-                        fragment.name().to_owned(),
-                    )
-                })?;
+                .clone();
+
+            let node_fixups = local_fixups
+                .and_then(|lf| lf.child(fragment.name()))
+                .and_then(|lf| lf.child("__overlay__"));
+            if let Some(node_fixups) = node_fixups {
+                apply_local_fixups(&mut content, node_fixups, phandle_offset)?;
+            }
+            bump_declared_phandles(&mut content, phandle_offset);
 
-            merge_nodes(&mut phandle_map, target_node, overlay_node)?;
+            let mut target_override = None;
+            if let Some(fixups) = fixups {
+                target_override = apply_external_fixups(
+                    self,
+                    fragment.name(),
+                    &mut content,
+                    fixups,
+                    &mut next_auto_phandle,
+                )?;
+            }
+
+            let target = resolve_target(self, fragment, target_override)?;
+            merge_nodes(target, &content);
         }
 
         Ok(())
     }
 }
 
-fn merge_nodes(
-    phandle_map: &mut PhandleMap,
-    existing: &mut DeviceTreeNode,
-    new: DeviceTreeNode,
-) -> Result<(), OverlayError> {
-    for mut prop in new.properties.into_values() {
-        phandle_map.fixup_property(&mut prop, existing.name())?;
+fn is_special(name: &str) -> bool {
+    matches!(name, "__symbols__" | "__fixups__" | "__local_fixups__")
+}
+
+fn resolve_target<'a>(
+    base: &'a mut DeviceTreeNode,
+    fragment: &DeviceTreeNode,
+    phandle_override: Option<u32>,
+) -> Result<&'a mut DeviceTreeNode, OverlayError> {
+    if let Some(target_path) = fragment.property("target-path") {
+        let path = target_path.as_string().map_err(|_err| {
+            OverlayError::new(OverlayErrorKind::TargetPathInvalid, fragment.name().to_owned())
+        })?;
+        return base.node_at_path_mut(path).ok_or_else(|| {
+            OverlayError::new(OverlayErrorKind::TargetNodeNotFound, fragment.name().to_owned())
+        });
+    }
+    // `dtc` compiles `target = <&label>;` to a placeholder `target` value of
+    // 0 plus a `__fixups__` entry (since the label's real phandle isn't
+    // known until the overlay is applied to a base tree), so prefer a
+    // fixup-resolved phandle over whatever `target` currently holds.
+    if let Some(phandle) = phandle_override {
+        return base.resolve_phandle_mut(phandle).ok_or_else(|| {
+            OverlayError::new(OverlayErrorKind::TargetNodeNotFound, fragment.name().to_owned())
+        });
+    }
+    if let Some(target) = fragment.property("target") {
+        let phandle = target.as_u32().map_err(|_err| {
+            OverlayError::new(OverlayErrorKind::CorruptedPhandle, fragment.name().to_owned())
+        })?;
+        return base.resolve_phandle_mut(phandle).ok_or_else(|| {
+            OverlayError::new(OverlayErrorKind::TargetNodeNotFound, fragment.name().to_owned())
+        });
+    }
+    Err(OverlayError::new(
+        OverlayErrorKind::TargetPathNotFound,
+        fragment.name().to_owned(),
+    ))
+}
 
+fn merge_nodes(existing: &mut DeviceTreeNode, new: &DeviceTreeNode) {
+    for prop in new.properties() {
         if let Some(existing_prop) = existing.property_mut(prop.name()) {
-            *existing_prop = prop;
+            *existing_prop = prop.clone();
         } else {
-            existing.add_property(prop);
+            existing.add_property(prop.clone());
         }
     }
 
-    for mut child in new.children.into_values() {
-        phandle_map.fixup_node(&mut child)?;
-
+    for child in new.children() {
         if let Some(existing_child) = existing.child_mut(child.name()) {
-            merge_nodes(phandle_map, existing_child, child)?;
+            merge_nodes(existing_child, child);
         } else {
-            existing.add_child(child);
+            existing.add_child(child.clone());
         }
     }
-    Ok(())
-}
-
-struct PhandleMap {
-    next_phandle: u32,
-    map: Vec<(u32, u32)>,
 }
 
-impl PhandleMap {
-    fn new(base: &DeviceTree) -> Result<Self, OverlayError> {
-        let mut max_phandle = 0;
-        let mut error = Ok(());
-        base.root().for_each_node(&mut |node| {
-            if let Some(phandle) = node.property("phandle") {
-                let Ok(phandle) = phandle.as_u32() else {
-                    error = Err(OverlayError::new(
-                        OverlayErrorKind::CorruptedPhandle,
-                        node.name().to_owned(),
-                    ));
-                    return;
-                };
-                if phandle > max_phandle {
-                    max_phandle = phandle;
-                }
-            }
-        });
-        error?;
+/// Walks a `__local_fixups__` subtree (mirroring the structure of the
+/// `__overlay__` content it describes) and, for each listed byte offset,
+/// adds `phandle_offset` to the big-endian `u32` phandle reference found
+/// there.
+fn apply_local_fixups(
+    content: &mut DeviceTreeNode,
+    fixups: &DeviceTreeNode,
+    phandle_offset: u32,
+) -> Result<(), OverlayError> {
+    for prop in fixups.properties() {
+        let offsets = prop.as_u32_array().map_err(|_err| {
+            OverlayError::new(OverlayErrorKind::CorruptedPhandle, content.name().to_owned())
+        })?;
+        let target_prop = content.property_mut(prop.name()).ok_or_else(|| {
+            OverlayError::new(OverlayErrorKind::PhandleNotFound, content.name().to_owned())
+        })?;
+        let mut value = target_prop.value().to_vec();
+        for offset in offsets {
+            patch_phandle_cell(&mut value, offset as usize, |old| old + phandle_offset).ok_or_else(
+                || OverlayError::new(OverlayErrorKind::CorruptedPhandle, content.name().to_owned()),
+            )?;
+        }
+        target_prop.set_value(value);
+    }
 
-        Ok(Self {
-            next_phandle: max_phandle + 1,
-            map: Vec::new(),
-        })
+    for fixup_child in fixups.children() {
+        let content_child = content.child_mut(fixup_child.name()).ok_or_else(|| {
+            OverlayError::new(OverlayErrorKind::SourceNodeNotFound, fixup_child.name().to_owned())
+        })?;
+        apply_local_fixups(content_child, fixup_child, phandle_offset)?;
     }
+    Ok(())
+}
 
-    fn fixup_node(&mut self, node: &mut DeviceTreeNode) -> Result<(), OverlayError> {
-        if let Some(phandle) = node.property("phandle") {
-            let phandle = phandle.as_u32().map_err(|_| {
-                OverlayError::new(OverlayErrorKind::CorruptedPhandle, node.name().to_owned())
-            })?;
-            let new_phandle = self.next_phandle;
-            self.next_phandle += 1;
-            self.map.push((phandle, new_phandle));
-            let node_name = node.name().to_owned();
-            node.property_mut("phandle")
-                .ok_or_else(|| OverlayError::new(OverlayErrorKind::PhandleNotFound, node_name))?
-                .set_value(new_phandle.to_be_bytes());
+/// Offsets every `phandle`/`linux,phandle` property this overlay subtree
+/// declares, so they don't collide with the base tree's existing phandles.
+///
+/// This is a flat `+= phandle_offset` on each declared value rather than a
+/// lookup through an old-to-new map, so unlike a remap table shared across
+/// many fixup sites, rebasing here costs a constant amount of work per
+/// phandle regardless of how many other phandles the overlay declares.
+fn bump_declared_phandles(node: &mut DeviceTreeNode, phandle_offset: u32) {
+    for name in ["phandle", "linux,phandle"] {
+        let Some(prop) = node.property_mut(name) else {
+            continue;
+        };
+        if let Ok(value) = prop.as_u32() {
+            prop.set_value((value + phandle_offset).to_be_bytes());
         }
+    }
+    for child in node.children_mut() {
+        bump_declared_phandles(child, phandle_offset);
+    }
+}
 
-        let node_name = node.name().to_owned();
-        for prop in node.properties_mut() {
-            self.fixup_property(prop, &node_name)?;
-        }
+/// Applies the entries of the overlay's top-level `__fixups__` node that
+/// target `fragment_name`'s `__overlay__` content, resolving each label
+/// against `base`'s `__symbols__` node (allocating a phandle on the target
+/// if it doesn't already have one).
+///
+/// An entry that targets `fragment_name`'s own `target` property (rather
+/// than its `__overlay__` content) isn't patched in place, since the
+/// fragment itself is an immutable borrow here; instead its resolved
+/// phandle is returned, for the caller to use in place of `target`.
+fn apply_external_fixups(
+    base: &mut DeviceTreeNode,
+    fragment_name: &str,
+    content: &mut DeviceTreeNode,
+    fixups: &DeviceTreeNode,
+    next_auto_phandle: &mut u32,
+) -> Result<Option<u32>, OverlayError> {
+    let overlay_prefix = alloc::format!("/{fragment_name}/__overlay__");
+    let target_path = alloc::format!("/{fragment_name}/target");
+    let mut target_override = None;
 
-        for child in node.children_mut() {
-            self.fixup_node(child)?;
-        }
-        Ok(())
-    }
+    for prop in fixups.properties() {
+        let label = prop.name();
+        let entries = prop
+            .as_stringlist()
+            .map_err(|_err| OverlayError::new(OverlayErrorKind::CorruptedPhandle, label.to_owned()))?;
 
-    fn fixup_property(
-        &mut self,
-        prop: &mut crate::model::DeviceTreeProperty,
-        node_name: &str,
-    ) -> Result<(), OverlayError> {
-        if !prop.value().len().is_multiple_of(4) {
-            return Ok(());
-        }
+        for entry in entries {
+            let mut parts = entry.splitn(3, ':');
+            let (Some(path), Some(prop_name), Some(offset)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
 
-        let mut new_value = prop.value().to_vec();
-        for (old, new) in &self.map {
-            for chunk in new_value.chunks_mut(4) {
-                let value = u32::from_be_bytes(chunk.try_into().map_err(|_| {
-                    OverlayError::new(OverlayErrorKind::CorruptedPhandle, node_name.to_owned())
-                })?);
-                if value == *old {
-                    chunk.copy_from_slice(&new.to_be_bytes());
-                }
+            if path == target_path && prop_name == "target" {
+                target_override = Some(resolve_label_phandle(base, label, next_auto_phandle)?);
+                continue;
             }
+
+            let Some(relative) = path.strip_prefix(&overlay_prefix) else {
+                continue;
+            };
+            let offset: usize = offset
+                .parse()
+                .map_err(|_err| OverlayError::new(OverlayErrorKind::CorruptedPhandle, label.to_owned()))?;
+
+            let target_phandle = resolve_label_phandle(base, label, next_auto_phandle)?;
+
+            let node = if relative.is_empty() {
+                &mut *content
+            } else {
+                content.node_at_path_mut(relative).ok_or_else(|| {
+                    OverlayError::new(OverlayErrorKind::SourceNodeNotFound, label.to_owned())
+                })?
+            };
+            let value_prop = node
+                .property_mut(prop_name)
+                .ok_or_else(|| OverlayError::new(OverlayErrorKind::PhandleNotFound, label.to_owned()))?;
+            let mut value = value_prop.value().to_vec();
+            patch_phandle_cell(&mut value, offset, |_old| target_phandle)
+                .ok_or_else(|| OverlayError::new(OverlayErrorKind::CorruptedPhandle, label.to_owned()))?;
+            value_prop.set_value(value);
         }
-        prop.set_value(new_value);
-        Ok(())
     }
+    Ok(target_override)
 }
 
-impl DeviceTreeNode {
-    fn for_each_node<F>(&self, f: &mut F)
-    where
-        F: FnMut(&DeviceTreeNode),
-    {
-        f(self);
-        for child in self.children() {
-            child.for_each_node(f);
-        }
+fn resolve_label_phandle(
+    base: &mut DeviceTreeNode,
+    label: &str,
+    next_auto_phandle: &mut u32,
+) -> Result<u32, OverlayError> {
+    let path = base
+        .node_at_path("/__symbols__")
+        .and_then(|symbols| symbols.property(label))
+        .and_then(|prop| prop.as_string().ok())
+        .ok_or_else(|| OverlayError::new(OverlayErrorKind::SymbolNotFound, label.to_owned()))?
+        .to_string();
+    let target = base
+        .node_at_path_mut(&path)
+        .ok_or_else(|| OverlayError::new(OverlayErrorKind::TargetNodeNotFound, label.to_owned()))?;
+
+    if let Some(phandle) = target.property("phandle").and_then(|prop| prop.as_u32().ok()) {
+        return Ok(phandle);
     }
+
+    let phandle = *next_auto_phandle;
+    *next_auto_phandle += 1;
+    target.add_property(DeviceTreeProperty::from_u32("phandle", phandle));
+    Ok(phandle)
+}
+
+/// Replaces the big-endian `u32` cell at `offset` in `value` by applying
+/// `f` to its current contents. Returns `None` if `offset` doesn't land on a
+/// whole cell within `value`.
+fn patch_phandle_cell(value: &mut [u8], offset: usize, f: impl FnOnce(u32) -> u32) -> Option<()> {
+    let cell = value.get_mut(offset..offset + 4)?;
+    let old = u32::from_be_bytes(cell.try_into().ok()?);
+    cell.copy_from_slice(&f(old).to_be_bytes());
+    Some(())
 }
@@ -6,7 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::{error::Error, fdt::FdtProperty};
+use crate::error::{FdtError, FdtErrorKind};
+use crate::fdt::FdtProperty;
 use alloc::{
     string::{String, ToString},
     vec::Vec,
@@ -19,6 +20,53 @@ pub struct DeviceTreeProperty {
     value: Vec<u8>,
 }
 
+/// A typed view of a property's value, classified using the same heuristics
+/// [`crate::fdt::PropertyValue`] does.
+///
+/// This lets callers match on the value's likely shape instead of
+/// speculatively calling [`DeviceTreeProperty::as_u32`]/
+/// [`DeviceTreeProperty::as_string`]/etc. and discarding the error on a
+/// mismatch. [`DeviceTreeProperty::value`] remains available as a raw
+/// fallback regardless of how a value classifies.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum PropertyValue<'a> {
+    /// The value is empty, e.g. a boolean property like `foo;`.
+    Empty,
+    /// The value is exactly 4 bytes, interpreted as a big-endian `u32`.
+    U32(u32),
+    /// The value is exactly 8 bytes, interpreted as a big-endian `u64`.
+    U64(u64),
+    /// The value is a single NUL-terminated, printable string.
+    Str(&'a str),
+    /// The value is more than one NUL-terminated, printable string
+    /// concatenated together.
+    StrList(core::str::Split<'a, char>),
+    /// The value's length is a multiple of 4 bytes but isn't 4 or 8, e.g. a
+    /// cell array like `interrupts` or `clocks`. See
+    /// [`DeviceTreeProperty::as_u32_array`] to decode it.
+    Cells(&'a [u8]),
+    /// The value didn't match any of the above; opaque raw bytes.
+    Bytes(&'a [u8]),
+}
+
+impl PartialEq for PropertyValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Empty, Self::Empty) => true,
+            (Self::U32(a), Self::U32(b)) => a == b,
+            (Self::U64(a), Self::U64(b)) => a == b,
+            (Self::Str(a), Self::Str(b)) => a == b,
+            // `Split` has no `PartialEq` impl of its own, so compare the
+            // strings it yields instead of its internal state.
+            (Self::StrList(a), Self::StrList(b)) => a.clone().eq(b.clone()),
+            (Self::Cells(a), Self::Cells(b)) => a == b,
+            (Self::Bytes(a), Self::Bytes(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
 impl DeviceTreeProperty {
     /// Creates a new `DeviceTreeProperty` with the given name and value.
     ///
@@ -38,6 +86,98 @@ impl DeviceTreeProperty {
         }
     }
 
+    /// Creates a new `DeviceTreeProperty` from a single big-endian `u32`
+    /// cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::from_u32("my-prop", 1234);
+    /// assert_eq!(prop.as_u32(), Ok(1234));
+    /// ```
+    #[must_use]
+    pub fn from_u32(name: impl Into<String>, value: u32) -> Self {
+        Self::new(name, value.to_be_bytes())
+    }
+
+    /// Creates a new `DeviceTreeProperty` from a pair of big-endian `u32`
+    /// cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::from_u64("my-prop", 0x1122334455667788);
+    /// assert_eq!(prop.as_u64(), Ok(0x1122334455667788));
+    /// ```
+    #[must_use]
+    pub fn from_u64(name: impl Into<String>, value: u64) -> Self {
+        Self::new(name, value.to_be_bytes())
+    }
+
+    /// Creates a new `DeviceTreeProperty` from a NUL-terminated string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::from_string("compatible", "arm,foo");
+    /// assert_eq!(prop.as_string(), Ok("arm,foo"));
+    /// ```
+    #[must_use]
+    pub fn from_string(name: impl Into<String>, value: impl AsRef<str>) -> Self {
+        let mut bytes = value.as_ref().as_bytes().to_vec();
+        bytes.push(0);
+        Self::new(name, bytes)
+    }
+
+    /// Creates a new `DeviceTreeProperty` from a list of NUL-separated
+    /// strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::from_stringlist("compatible", ["arm,foo", "arm,bar"]);
+    /// let strings: Vec<_> = prop.as_stringlist().unwrap().collect();
+    /// assert_eq!(strings, ["arm,foo", "arm,bar"]);
+    /// ```
+    #[must_use]
+    pub fn from_stringlist(
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Self {
+        let mut bytes = Vec::new();
+        for value in values {
+            bytes.extend_from_slice(value.as_ref().as_bytes());
+            bytes.push(0);
+        }
+        Self::new(name, bytes)
+    }
+
+    /// Creates a new `DeviceTreeProperty` from a list of big-endian `u32`
+    /// cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::from_cells("interrupts", [0, 1, 4]);
+    /// assert_eq!(
+    ///     prop.as_u32_array().unwrap().collect::<Vec<_>>(),
+    ///     [0, 1, 4]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_cells(name: impl Into<String>, cells: impl IntoIterator<Item = u32>) -> Self {
+        let mut bytes = Vec::new();
+        for cell in cells {
+            bytes.extend_from_slice(&cell.to_be_bytes());
+        }
+        Self::new(name, bytes)
+    }
+
     /// Returns the name of this property.
     #[must_use]
     pub fn name(&self) -> &str {
@@ -66,6 +206,11 @@ impl DeviceTreeProperty {
 
     /// Returns the value of this property as a `u32`.
     ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// not 4 bytes long.
+    ///
     /// # Examples
     ///
     /// ```
@@ -73,32 +218,309 @@ impl DeviceTreeProperty {
     /// let prop = DeviceTreeProperty::new("my-prop", 1234u32.to_be_bytes());
     /// assert_eq!(prop.as_u32(), Ok(1234));
     /// ```
-    pub fn as_u32(&self) -> Result<u32, ()> {
+    pub fn as_u32(&self) -> Result<u32, FdtError> {
         self.value
             .as_slice()
             .try_into()
             .map(u32::from_be_bytes)
-            .map_err(|_| ())
+            .map_err(|_| FdtError::new(FdtErrorKind::InvalidLength, 0))
+    }
+
+    /// Returns the value of this property as a `u64`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// not 8 bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::new("my-prop", 0x1122334455667788u64.to_be_bytes());
+    /// assert_eq!(prop.as_u64(), Ok(0x1122334455667788));
+    /// ```
+    pub fn as_u64(&self) -> Result<u64, FdtError> {
+        self.value
+            .as_slice()
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| FdtError::new(FdtErrorKind::InvalidLength, 0))
+    }
+
+    /// Returns the value of this property as a phandle id.
+    ///
+    /// Like [`DeviceTreeProperty::as_u32`], but additionally rejects the
+    /// values `0` and `0xffffffff`, which the Devicetree specification
+    /// reserves and which therefore never identify a real node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// not 4 bytes long, or an [`FdtErrorKind::BadPhandle`] if it holds a
+    /// reserved phandle value.
+    pub fn as_phandle(&self) -> Result<u32, FdtError> {
+        match self.as_u32()? {
+            0 | 0xffff_ffff => Err(FdtError::new(FdtErrorKind::BadPhandle, 0)),
+            phandle => Ok(phandle),
+        }
     }
 
     /// Returns the value of this property as a string.
     ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidString`] if the property's value is
+    /// not a NUL-terminated string, contains an interior NUL, or is not
+    /// valid UTF-8.
+    ///
     /// # Examples
     ///
     /// ```
     /// # use ritm_device_tree::model::DeviceTreeProperty;
-    /// let prop = DeviceTreeProperty::new("my-prop", "hello");
-    /// assert_eq!(prop.as_str(), Ok("hello"));
+    /// let prop = DeviceTreeProperty::from_string("my-prop", "hello");
+    /// assert_eq!(prop.as_string(), Ok("hello"));
     /// ```
-    pub fn as_str(&self) -> Result<&str, ()> {
+    pub fn as_string(&self) -> Result<&str, FdtError> {
+        let cstr = core::ffi::CStr::from_bytes_with_nul(&self.value)
+            .map_err(|_| FdtError::new(FdtErrorKind::InvalidString, 0))?;
+        cstr.to_str()
+            .map_err(|_| FdtError::new(FdtErrorKind::InvalidString, 0))
+    }
+
+    /// Returns an iterator over the strings in this property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidString`] if the property's value
+    /// contains invalid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::from_stringlist("compatible", ["arm,foo", "arm,bar"]);
+    /// let strings: Vec<_> = prop.as_stringlist().unwrap().collect();
+    /// assert_eq!(strings, ["arm,foo", "arm,bar"]);
+    /// ```
+    pub fn as_stringlist(&self) -> Result<impl Iterator<Item = &str>, FdtError> {
         core::str::from_utf8(&self.value)
-            .map(|s| s.trim_end_matches('\0'))
-            .map_err(|_| ())
+            .map_err(|_| FdtError::new(FdtErrorKind::InvalidString, 0))
+            .map(|s| s.trim_end_matches('\0').split('\0'))
+    }
+
+    /// Classifies this property's value, using the same heuristics
+    /// [`crate::fdt::PropertyValue`] does: all bytes printable ASCII or NUL,
+    /// NUL-terminated, and no embedded empty string classifies it as a
+    /// string or string list; otherwise a length that's a multiple of 4
+    /// bytes classifies it as `U32`/`U64`/`Cells`; anything else is raw
+    /// bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::{DeviceTreeProperty, PropertyValue};
+    /// let prop = DeviceTreeProperty::from_string("my-prop", "hello");
+    /// assert_eq!(prop.parsed(), PropertyValue::Str("hello"));
+    /// ```
+    #[must_use]
+    pub fn parsed(&self) -> PropertyValue<'_> {
+        if self.value.is_empty() {
+            return PropertyValue::Empty;
+        }
+
+        let is_printable = self
+            .value
+            .iter()
+            .all(|&ch| ch.is_ascii_graphic() || ch == b' ' || ch == 0);
+        let has_empty = self.value.windows(2).any(|window| window == [0, 0]);
+        if is_printable
+            && self.value.ends_with(&[0])
+            && !has_empty
+            && let Ok(mut strings) = self.as_stringlist()
+            && let Some(first) = strings.next()
+        {
+            return if strings.next().is_some() {
+                PropertyValue::StrList(
+                    core::str::from_utf8(&self.value)
+                        .expect("just succeeded above")
+                        .trim_end_matches('\0')
+                        .split('\0'),
+                )
+            } else {
+                PropertyValue::Str(first)
+            };
+        }
+
+        match self.value.len() {
+            4 => PropertyValue::U32(self.as_u32().expect("length just checked")),
+            8 => PropertyValue::U64(self.as_u64().expect("length just checked")),
+            len if len.is_multiple_of(4) => PropertyValue::Cells(&self.value),
+            _ => PropertyValue::Bytes(&self.value),
+        }
+    }
+
+    /// Returns an iterator over the big-endian `u32` cells in this
+    /// property's value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value
+    /// length is not a multiple of 4 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ritm_device_tree::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::from_cells("interrupts", [0, 1, 4]);
+    /// assert_eq!(
+    ///     prop.as_u32_array().unwrap().collect::<Vec<_>>(),
+    ///     [0, 1, 4]
+    /// );
+    /// ```
+    pub fn as_u32_array(&self) -> Result<impl Iterator<Item = u32> + '_, FdtError> {
+        if !self.value.len().is_multiple_of(4) {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        }
+        Ok(self
+            .value
+            .chunks_exact(4)
+            .map(|cell| u32::from_be_bytes(cell.try_into().expect("chunk is 4 bytes"))))
+    }
+
+    /// Returns the value of this property decoded as a `reg`/`ranges`-style
+    /// property, yielding `(address, size)` tuples. Pair this with
+    /// [`DeviceTreeNode::address_cells`](crate::model::DeviceTreeNode::address_cells)/
+    /// [`size_cells`](crate::model::DeviceTreeNode::size_cells) on the
+    /// property's parent node to get the cell counts it needs.
+    ///
+    /// `address_cells` and `size_cells` are not self-describing in the
+    /// property bytes; they normally come from the `#address-cells`/
+    /// `#size-cells` properties of the parent node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if `address_cells` or
+    /// `size_cells` is greater than 2 (which wouldn't fit in a `u64`), or if
+    /// the property's value is not a multiple of `(address_cells +
+    /// size_cells) * 4` bytes long.
+    pub fn as_reg(
+        &self,
+        address_cells: u32,
+        size_cells: u32,
+    ) -> Result<impl Iterator<Item = (u64, Option<u64>)> + '_, FdtError> {
+        if address_cells > 2 || size_cells > 2 {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        }
+        let entry_size = (address_cells + size_cells) as usize * 4;
+        if entry_size == 0 || !self.value.len().is_multiple_of(entry_size) {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        }
+        Ok(self.value.chunks_exact(entry_size).map(move |entry| {
+            let (address, rest) = fold_cells(entry, address_cells);
+            let size = (size_cells > 0).then(|| fold_cells(rest, size_cells).0);
+            (address, size)
+        }))
+    }
+
+    /// Returns the value of this property decoded as a `ranges` property,
+    /// yielding `(child_bus_address, parent_bus_address, length)` tuples.
+    ///
+    /// `child_address_cells` and `parent_address_cells` are not
+    /// self-describing in the property bytes; they normally come from this
+    /// node's own `#address-cells` and its parent's `#address-cells`,
+    /// respectively, and `size_cells` from this node's own `#size-cells`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if any cell count is
+    /// greater than 2 (which wouldn't fit in a `u64`), or if the property's
+    /// value is not a multiple of `(child_address_cells +
+    /// parent_address_cells + size_cells) * 4` bytes long.
+    pub fn as_ranges(
+        &self,
+        child_address_cells: u32,
+        parent_address_cells: u32,
+        size_cells: u32,
+    ) -> Result<impl Iterator<Item = (u64, u64, u64)> + '_, FdtError> {
+        if child_address_cells > 2 || parent_address_cells > 2 || size_cells > 2 {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        }
+        let entry_size = (child_address_cells + parent_address_cells + size_cells) as usize * 4;
+        if entry_size == 0 || !self.value.len().is_multiple_of(entry_size) {
+            return Err(FdtError::new(FdtErrorKind::InvalidLength, 0));
+        }
+        Ok(self.value.chunks_exact(entry_size).map(move |entry| {
+            let (child_bus_address, rest) = fold_cells(entry, child_address_cells);
+            let (parent_bus_address, rest) = fold_cells(rest, parent_address_cells);
+            let (length, _rest) = fold_cells(rest, size_cells);
+            (child_bus_address, parent_bus_address, length)
+        }))
+    }
+}
+
+impl core::fmt::Display for DeviceTreeProperty {
+    /// Renders this property as a single DTS source line, e.g. `foo =
+    /// <0x01>;`, using the same value rendering [`Fdt`](crate::fdt::Fdt)'s
+    /// `Display` impl does.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.name)?;
+        match self.parsed() {
+            PropertyValue::Empty => write!(f, ";"),
+            PropertyValue::Str(s) => write!(f, " = \"{s}\";"),
+            PropertyValue::StrList(strings) => {
+                write!(f, " = ")?;
+                for (i, s) in strings.enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{s}\"")?;
+                }
+                write!(f, ";")
+            }
+            PropertyValue::U32(_) | PropertyValue::U64(_) | PropertyValue::Cells(_) => {
+                write!(f, " = <")?;
+                let cells = self
+                    .as_u32_array()
+                    .expect("parsed() only returns these variants when the length is a multiple of 4");
+                for (i, val) in cells.enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "0x{val:02x}")?;
+                }
+                write!(f, ">;")
+            }
+            PropertyValue::Bytes(bytes) => {
+                write!(f, " = [")?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{byte:02x}")?;
+                }
+                write!(f, "];")
+            }
+        }
+    }
+}
+
+/// Reads `cells` consecutive big-endian `u32`s from the front of `value` and
+/// folds them into a `u64`, most-significant cell first. Returns the value
+/// and the remaining slice.
+fn fold_cells(value: &[u8], cells: u32) -> (u64, &[u8]) {
+    let mut result = 0u64;
+    let mut rest = value;
+    for _ in 0..cells {
+        let (cell, remainder) = rest.split_first_chunk::<4>().expect("length was validated");
+        result = (result << 32) | u64::from(u32::from_be_bytes(*cell));
+        rest = remainder;
     }
+    (result, rest)
 }
 
 impl<'a> TryFrom<FdtProperty<'a>> for DeviceTreeProperty {
-    type Error = Error;
+    type Error = FdtError;
 
     fn try_from(prop: FdtProperty<'a>) -> Result<Self, Self::Error> {
         let name = prop.name().to_string();
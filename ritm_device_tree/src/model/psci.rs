@@ -0,0 +1,112 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Generates a `/psci` node conforming to the PSCI firmware bindings
+//! (`Documentation/devicetree/bindings/arm/psci.yaml`), so a tree built in
+//! memory gives a guest kernel's `smp.c`/cpuidle code everything it needs to
+//! call into PSCI without the caller hand-encoding the node itself.
+
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+
+/// The 32-bit function IDs a PSCI `0.1` node lists in its `cpu_suspend`/
+/// `cpu_off`/`cpu_on`/`migrate` properties. PSCI `0.1` defines no standard
+/// IDs of its own, so these are the values PSCI `0.2` fixed by
+/// specification; a `0.2` or later node lists no IDs at all.
+///
+/// These duplicate, rather than reuse, `src/exceptions/psci.rs`'s
+/// `psci_consts` module: that module lives in the `ritm` binary crate,
+/// which depends on `ritm_device_tree`, not the other way around, and its
+/// constants are `pub(super)` besides. Neither crate can reference the
+/// other's copy without either inverting that dependency or pulling the
+/// constants out into a third, shared crate — out of scope here. Until one
+/// of those happens, a change to either table has to be mirrored in the
+/// other by hand.
+mod function_ids {
+    const FN_BASE: u32 = 0x8400_0000;
+    pub(super) const CPU_SUSPEND: u32 = FN_BASE + 1;
+    pub(super) const CPU_OFF: u32 = FN_BASE + 2;
+    pub(super) const CPU_ON: u32 = FN_BASE + 3;
+    pub(super) const MIGRATE: u32 = FN_BASE + 5;
+}
+
+/// The conduit a guest uses to call into PSCI: either `HVC` (trapping to a
+/// hypervisor running at EL2) or `SMC` (trapping to firmware running at
+/// EL3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsciMethod {
+    Hvc,
+    Smc,
+}
+
+impl PsciMethod {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Hvc => "hvc",
+            Self::Smc => "smc",
+        }
+    }
+}
+
+/// Which revision of the PSCI specification a `/psci` node should advertise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsciVersion {
+    /// PSCI 0.1, which has no standard function IDs and must list them
+    /// explicitly via `cpu_suspend`/`cpu_off`/`cpu_on`/`migrate` properties.
+    V0_1,
+    /// PSCI 0.2, whose function IDs are fixed by the specification.
+    V0_2,
+    /// PSCI 1.0, a superset of 0.2 that also advertises 0.2 compatibility.
+    V1_0,
+}
+
+impl DeviceTree {
+    /// Adds a conformant `/psci` node to this tree.
+    ///
+    /// `method` selects the `method` property (`"hvc"` or `"smc"`);
+    /// `version` selects the `compatible` strings and, for
+    /// [`PsciVersion::V0_1`] only, the explicit `cpu_suspend`/`cpu_off`/
+    /// `cpu_on`/`migrate` function-id properties that version's layout
+    /// requires (0.2 and 1.0 fix their function IDs by specification, so no
+    /// properties are needed for those).
+    pub fn add_psci_node(&mut self, method: PsciMethod, version: PsciVersion) {
+        let mut psci = DeviceTreeNode::new("psci");
+
+        let compatible: &[&str] = match version {
+            PsciVersion::V0_1 => &["arm,psci"],
+            PsciVersion::V0_2 => &["arm,psci-0.2"],
+            PsciVersion::V1_0 => &["arm,psci-1.0", "arm,psci-0.2"],
+        };
+        psci.add_property(DeviceTreeProperty::from_stringlist("compatible", compatible));
+        psci.add_property(DeviceTreeProperty::from_string("method", method.as_str()));
+
+        if version == PsciVersion::V0_1 {
+            psci.add_property(DeviceTreeProperty::from_u32("cpu_suspend", function_ids::CPU_SUSPEND));
+            psci.add_property(DeviceTreeProperty::from_u32("cpu_off", function_ids::CPU_OFF));
+            psci.add_property(DeviceTreeProperty::from_u32("cpu_on", function_ids::CPU_ON));
+            psci.add_property(DeviceTreeProperty::from_u32("migrate", function_ids::MIGRATE));
+        }
+
+        self.root.add_child(psci);
+    }
+
+    /// Stamps every `/cpus/cpu@*` node with `enable-method = "psci"`, so a
+    /// guest kernel knows to use PSCI (rather than e.g. spin-tables) to
+    /// bring up secondary CPUs.
+    ///
+    /// Does nothing if there is no `/cpus` node yet.
+    pub fn mark_cpus_psci_enabled(&mut self) {
+        let Some(cpus) = self.root.child_mut("cpus") else {
+            return;
+        };
+        for cpu in cpus.children_mut() {
+            if cpu.name().starts_with("cpu@") {
+                cpu.add_property(DeviceTreeProperty::from_string("enable-method", "psci"));
+            }
+        }
+    }
+}
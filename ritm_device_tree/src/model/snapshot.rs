@@ -0,0 +1,128 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `serde`-based snapshot serialization for [`DeviceTree`], so a VMM can
+//! capture a live tree to a human-diffable intermediate form (e.g. JSON or
+//! CBOR) and restore it later, the way cloud-hypervisor snapshots its own
+//! `DeviceTree(HashMap<String, DeviceNode>)` across save/restore.
+//!
+//! This is a separate representation from the packed DTB [`DeviceTree::to_dtb`]
+//! produces: it's larger and slower to parse, but stable and readable, which
+//! makes it a better fit for config templating and migration state than a
+//! binary blob would be. Property values are encoded as base64 rather than
+//! passed through as raw bytes, since most `serde` formats (JSON in
+//! particular) have no native byte-string type and would otherwise mangle
+//! non-UTF8 values; round-tripping a tree through this module and then
+//! through [`DeviceTree::to_dtb`] reproduces the original blob exactly,
+//! since child/property order and memory reservations are preserved too.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use base64::Engine as _;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::MemoryReservation;
+
+impl Serialize for DeviceTreeProperty {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Shadow<'a> {
+            name: &'a str,
+            value: String,
+        }
+        Shadow {
+            name: self.name(),
+            value: base64::engine::general_purpose::STANDARD.encode(self.value()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceTreeProperty {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Shadow {
+            name: String,
+            value: String,
+        }
+        let shadow = Shadow::deserialize(deserializer)?;
+        let value = base64::engine::general_purpose::STANDARD
+            .decode(shadow.value)
+            .map_err(D::Error::custom)?;
+        Ok(DeviceTreeProperty::new(shadow.name, value))
+    }
+}
+
+impl Serialize for DeviceTreeNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Shadow<'a> {
+            name: &'a str,
+            properties: Vec<&'a DeviceTreeProperty>,
+            children: Vec<&'a DeviceTreeNode>,
+        }
+        Shadow {
+            name: self.name(),
+            properties: self.properties().collect(),
+            children: self.children().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceTreeNode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Shadow {
+            name: String,
+            properties: Vec<DeviceTreeProperty>,
+            children: Vec<DeviceTreeNode>,
+        }
+        let shadow = Shadow::deserialize(deserializer)?;
+        let mut node = DeviceTreeNode::new(shadow.name);
+        for property in shadow.properties {
+            node.add_property(property);
+        }
+        for child in shadow.children {
+            node.add_child(child);
+        }
+        Ok(node)
+    }
+}
+
+impl Serialize for DeviceTree {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Shadow<'a> {
+            root: &'a DeviceTreeNode,
+            memory_reservations: &'a [MemoryReservation],
+        }
+        Shadow {
+            root: self.root(),
+            memory_reservations: &self.memory_reservations,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceTree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Shadow {
+            root: DeviceTreeNode,
+            memory_reservations: Vec<MemoryReservation>,
+        }
+        let shadow = Shadow::deserialize(deserializer)?;
+        let mut tree = DeviceTree::new(shadow.root);
+        tree.memory_reservations = shadow.memory_reservations;
+        Ok(tree)
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Synthesizes a `/cpus` subtree and its `cpu-map`, per the ARM CPU
+//! topology bindings (`Documentation/devicetree/bindings/arm/cpu-map.yaml`),
+//! from a high-level cluster/core description instead of requiring callers
+//! to hand-encode the nested affinity hierarchy `topology.c` parses.
+
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+
+impl DeviceTree {
+    /// Builds `/cpus` and `/cpus/cpu-map` from `clusters`, where
+    /// `clusters[c][i]` is the MPIDR affinity value (the `reg` this CPU
+    /// should be addressed by) of core `i` in cluster `c`.
+    ///
+    /// For each core this creates a `cpu@<affinity>` node under `/cpus`
+    /// (`device_type = "cpu"`, a two-cell `reg` holding the affinity value,
+    /// and a freshly allocated `phandle`), and a matching
+    /// `cpu-map/cluster<c>/core<i>` node whose `cpu` property references
+    /// that phandle, so a scheduler walking `cpu-map` resolves straight back
+    /// to the real CPU node.
+    ///
+    /// Pair this with [`DeviceTree::mark_cpus_psci_enabled`] if the guest
+    /// brings up secondary CPUs via PSCI.
+    pub fn build_cpu_topology(&mut self, clusters: &[&[u64]]) {
+        if self.root.child("cpus").is_none() {
+            self.root.add_child(DeviceTreeNode::new("cpus"));
+        }
+        {
+            let cpus = self.root.child_mut("cpus").expect("just ensured above");
+            cpus.add_property(DeviceTreeProperty::from_u32("#address-cells", 2));
+            cpus.add_property(DeviceTreeProperty::from_u32("#size-cells", 0));
+        }
+
+        let mut cpu_map = DeviceTreeNode::new("cpu-map");
+        for (cluster_index, cores) in clusters.iter().enumerate() {
+            let mut cluster = DeviceTreeNode::new(alloc::format!("cluster{cluster_index}"));
+            for (core_index, &affinity) in cores.iter().enumerate() {
+                // Allocated and committed to the tree before the next
+                // iteration's call, satisfying `allocate_phandle`'s
+                // requirement that the returned value be assigned before
+                // it's called again.
+                let phandle = self.allocate_phandle();
+
+                let mut cpu = DeviceTreeNode::new(alloc::format!("cpu@{affinity:x}"));
+                cpu.add_property(DeviceTreeProperty::from_string("device_type", "cpu"));
+                cpu.add_property(DeviceTreeProperty::from_string("compatible", "arm,armv8"));
+                cpu.add_property(DeviceTreeProperty::new("reg", affinity.to_be_bytes()));
+                cpu.add_property(DeviceTreeProperty::from_u32("phandle", phandle));
+                self.root
+                    .child_mut("cpus")
+                    .expect("just ensured above")
+                    .add_child(cpu);
+
+                let mut core = DeviceTreeNode::new(alloc::format!("core{core_index}"));
+                core.add_property(DeviceTreeProperty::from_u32("cpu", phandle));
+                cluster.add_child(core);
+            }
+            cpu_map.add_child(cluster);
+        }
+        self.root
+            .child_mut("cpus")
+            .expect("just ensured above")
+            .add_child(cpu_map);
+
+        self.rebuild_index();
+    }
+}
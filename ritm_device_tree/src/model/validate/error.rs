@@ -0,0 +1,137 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use alloc::string::String;
+use core::fmt;
+
+/// A single structural problem found by [`DeviceTree::validate`](crate::model::DeviceTree::validate).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ValidationError {
+    path: String,
+    /// The type of the problem that was found.
+    pub kind: ValidationErrorKind,
+    /// Whether this is a hard structural error, or merely a convention `dtc`
+    /// itself only warns about.
+    pub severity: Severity,
+}
+
+impl ValidationError {
+    pub(crate) fn new(kind: ValidationErrorKind, path: String, severity: Severity) -> Self {
+        Self { path, kind, severity }
+    }
+
+    /// Returns the path of the node the problem was found on.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Whether a [`ValidationError`] is a hard structural error, or merely a
+/// convention `dtc` itself only warns about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Severity {
+    /// A structural problem that would make the tree behave incorrectly or
+    /// produce a blob other tools would reject.
+    Error,
+    /// A convention violation `dtc` itself only warns about, e.g. a node's
+    /// name and its `reg` property disagreeing about its unit address.
+    Warning,
+}
+
+/// The kind of a structural problem found by
+/// [`DeviceTree::validate`](crate::model::DeviceTree::validate).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ValidationErrorKind {
+    /// Two children of the same node share a name.
+    DuplicateSiblingName(String),
+    /// The node's name has a `@unit-address` suffix, but its `reg`
+    /// property's first address cell does not match it.
+    UnitAddressMismatch,
+    /// The node has a `reg` property, but its name has no `@unit-address`
+    /// suffix.
+    MissingUnitAddress,
+    /// The node's name has a `@unit-address` suffix, but it has neither a
+    /// `reg` nor a `ranges` property.
+    MissingReg,
+    /// A `phandle`/`linux,phandle` property's value is not exactly 4 bytes.
+    InvalidPhandleLength,
+    /// A `phandle`/`linux,phandle` property's value is 0 or `0xffffffff`,
+    /// both of which are reserved and never assigned to a real node.
+    ReservedPhandle,
+    /// Two nodes declare the same `phandle`/`linux,phandle` value.
+    DuplicatePhandle(u32),
+    /// An `interrupt-parent` property refers to a phandle value no node in
+    /// the tree declares.
+    DanglingPhandleReference(u32),
+    /// An `#address-cells` or `#size-cells` property's value is not exactly
+    /// a single 4-byte cell.
+    InvalidAddressOrSizeCells,
+    /// A node or property name uses a character outside the set the
+    /// devicetree specification allows.
+    InvalidName,
+    /// A `reg` or `ranges` property's length is not a whole multiple of the
+    /// entry size its applicable `#address-cells`/`#size-cells` imply.
+    RegRangesCellMismatch,
+    /// A path listed in [`ValidationConstraints::required_nodes`](crate::model::ValidationConstraints::required_nodes)
+    /// does not exist in the tree.
+    MissingRequiredNode(String),
+    /// Two of the tree's memory reservations overlap.
+    MemoryReservationOverlap,
+    /// A memory reservation falls outside the bounds configured in
+    /// [`ValidationConstraints::address_bounds`](crate::model::ValidationConstraints::address_bounds).
+    MemoryReservationOutOfBounds,
+    /// A node has a child with a `reg` property, but the node itself has no
+    /// `#address-cells`/`#size-cells` property governing how to decode it.
+    MissingAddressSizeCells,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at `{}`", self.kind, self.path)
+    }
+}
+
+impl fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateSiblingName(name) => write!(f, "duplicate sibling node name `{name}`"),
+            Self::UnitAddressMismatch => {
+                write!(f, "unit address does not match the first `reg` address cell")
+            }
+            Self::MissingUnitAddress => write!(f, "node has a `reg` property but no unit address"),
+            Self::MissingReg => write!(f, "node has a unit address but no `reg` or `ranges` property"),
+            Self::InvalidPhandleLength => write!(f, "phandle property value is not a single u32 cell"),
+            Self::ReservedPhandle => write!(f, "phandle value is reserved (0 or 0xffffffff)"),
+            Self::DuplicatePhandle(phandle) => write!(f, "duplicate phandle value {phandle:#x}"),
+            Self::DanglingPhandleReference(phandle) => {
+                write!(f, "reference to undeclared phandle {phandle:#x}")
+            }
+            Self::InvalidAddressOrSizeCells => {
+                write!(f, "#address-cells/#size-cells value is not a single u32 cell")
+            }
+            Self::InvalidName => write!(f, "name contains a character outside the allowed set"),
+            Self::RegRangesCellMismatch => {
+                write!(f, "reg/ranges property length doesn't match its address/size cell counts")
+            }
+            Self::MissingRequiredNode(path) => write!(f, "required node `{path}` is missing"),
+            Self::MemoryReservationOverlap => write!(f, "memory reservations overlap"),
+            Self::MemoryReservationOutOfBounds => {
+                write!(f, "memory reservation falls outside the configured address bounds")
+            }
+            Self::MissingAddressSizeCells => {
+                write!(f, "node has a child with a `reg` property but declares no #address-cells/#size-cells")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ValidationError {}
@@ -0,0 +1,382 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A structural validation pass over a [`DeviceTree`], modeled on `dtc`'s own
+//! `checks.c`.
+
+use alloc::collections::btree_map::BTreeMap;
+use alloc::collections::btree_set::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::memreserve::MemoryReservation;
+use crate::model::{DeviceTree, DeviceTreeNode};
+
+mod error;
+pub use error::{Severity, ValidationError, ValidationErrorKind};
+
+/// Extra invariants for [`DeviceTree::validate`] to check, on top of the
+/// structural checks it always performs.
+///
+/// The default value checks nothing extra: no required nodes, no address
+/// bounds.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidationConstraints<'a> {
+    /// Absolute paths that must exist in the tree, e.g. `"/chosen"`.
+    pub required_nodes: &'a [&'a str],
+    /// If set, every memory reservation's `address..address + size` must
+    /// fall within this `(start, end)` range.
+    pub address_bounds: Option<(u64, u64)>,
+}
+
+impl DeviceTree {
+    /// Checks this tree for the structural problems a devicetree compiler
+    /// would flag, without needing to round-trip through [`DeviceTree::to_dtb`]
+    /// first.
+    ///
+    /// Unlike [`DeviceTree::to_dtb`], which only panics on values too large
+    /// to encode, this walks the whole tree and reports every problem it
+    /// finds rather than stopping at the first one, so callers can decide
+    /// for themselves which [`Severity`] to treat as fatal.
+    ///
+    /// Checks performed:
+    /// - Sibling node names are unique.
+    /// - A node named `foo@unit` has a `reg` or `ranges` property, and a
+    ///   node with a `reg` property is named `foo@unit` (warnings).
+    /// - A node named `foo@unit` with a `reg` property has `unit` match the
+    ///   `reg` property's first address cell (a warning).
+    /// - Every `phandle`/`linux,phandle` property is a single `u32` cell,
+    ///   unique across the tree, and neither `0` nor `0xffffffff`.
+    /// - Every `interrupt-parent` property refers to a phandle some node in
+    ///   the tree actually declares.
+    /// - Every `#address-cells`/`#size-cells` property is a single `u32`
+    ///   cell.
+    /// - A node with at least one child that has a `reg` property itself
+    ///   declares `#address-cells`/`#size-cells` (a warning), rather than
+    ///   relying on the defaults [`DeviceTreeNode::address_cells`]/
+    ///   [`DeviceTreeNode::size_cells`] fall back to.
+    /// - Node and property names use only the character set the devicetree
+    ///   specification allows.
+    /// - Every `reg`/non-empty `ranges` property's length is a whole number
+    ///   of entries, given the address/size cells that apply to it.
+    /// - Every path in `constraints.required_nodes` exists.
+    /// - The tree's memory reservations don't overlap, and (if
+    ///   `constraints.address_bounds` is set) fall within it.
+    ///
+    /// # Errors
+    ///
+    /// Returns every [`ValidationError`] found; returns `Ok(())` if none are
+    /// found. Errors found on individual nodes come first, in the order
+    /// their nodes appear in a depth-first walk of the tree, followed by
+    /// dangling `interrupt-parent` references, missing required nodes, and
+    /// memory reservation problems.
+    pub fn validate(&self, constraints: &ValidationConstraints) -> Result<(), Vec<ValidationError>> {
+        let mut phandles = BTreeMap::new();
+        let mut interrupt_parents = Vec::new();
+        let mut errors = Vec::new();
+
+        walk(
+            self.root(),
+            "/",
+            2,
+            1,
+            &mut phandles,
+            &mut interrupt_parents,
+            &mut errors,
+        );
+
+        for (path, phandle) in interrupt_parents {
+            if !phandles.contains_key(&phandle) {
+                errors.push(ValidationError::new(
+                    ValidationErrorKind::DanglingPhandleReference(phandle),
+                    path,
+                    Severity::Error,
+                ));
+            }
+        }
+
+        for &path in constraints.required_nodes {
+            if self.root().node_at_path(path).is_none() {
+                errors.push(ValidationError::new(
+                    ValidationErrorKind::MissingRequiredNode(path.to_string()),
+                    path.to_string(),
+                    Severity::Error,
+                ));
+            }
+        }
+
+        check_memory_reservations(&self.memory_reservations, constraints.address_bounds, &mut errors);
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// Checks that no two of `reservations` overlap, and (if `bounds` is set)
+/// that every reservation falls within it.
+fn check_memory_reservations(
+    reservations: &[MemoryReservation],
+    bounds: Option<(u64, u64)>,
+    errors: &mut Vec<ValidationError>,
+) {
+    if let Some((lower, upper)) = bounds {
+        for reservation in reservations {
+            let end = reservation.address().saturating_add(reservation.size());
+            if reservation.address() < lower || end > upper {
+                errors.push(ValidationError::new(
+                    ValidationErrorKind::MemoryReservationOutOfBounds,
+                    "/memreserve/".to_string(),
+                    Severity::Error,
+                ));
+            }
+        }
+    }
+
+    let mut sorted: Vec<MemoryReservation> = reservations.to_vec();
+    sorted.sort_by_key(MemoryReservation::address);
+    for pair in sorted.windows(2) {
+        if pair[0].address().saturating_add(pair[0].size()) > pair[1].address() {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::MemoryReservationOverlap,
+                "/memreserve/".to_string(),
+                Severity::Error,
+            ));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn walk(
+    node: &DeviceTreeNode,
+    path: &str,
+    address_cells: u32,
+    size_cells: u32,
+    phandles: &mut BTreeMap<u32, String>,
+    interrupt_parents: &mut Vec<(String, u32)>,
+    errors: &mut Vec<ValidationError>,
+) {
+    if !is_valid_name(node.name()) {
+        errors.push(ValidationError::new(
+            ValidationErrorKind::InvalidName,
+            path.to_string(),
+            Severity::Error,
+        ));
+    }
+    for property in node.properties() {
+        // Property names have no unit address, and may also use `?` and `#`
+        // (as in `#address-cells`).
+        let name = property.name();
+        let is_valid_property_name = !name.is_empty()
+            && name.bytes().all(|b| {
+                b.is_ascii_alphanumeric() || matches!(b, b',' | b'.' | b'_' | b'+' | b'?' | b'#' | b'-')
+            });
+        if !is_valid_property_name {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::InvalidName,
+                path.to_string(),
+                Severity::Error,
+            ));
+        }
+    }
+
+    for prop_name in ["#address-cells", "#size-cells"] {
+        if let Some(prop) = node.property(prop_name)
+            && prop.value().len() != 4
+        {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::InvalidAddressOrSizeCells,
+                path.to_string(),
+                Severity::Error,
+            ));
+        }
+    }
+
+    check_unit_address(node, path, address_cells, size_cells, errors);
+    check_reg_ranges_lengths(node, path, address_cells, size_cells, errors);
+
+    for prop_name in ["phandle", "linux,phandle"] {
+        if let Some(prop) = node.property(prop_name) {
+            if prop.value().len() != 4 {
+                errors.push(ValidationError::new(
+                    ValidationErrorKind::InvalidPhandleLength,
+                    path.to_string(),
+                    Severity::Error,
+                ));
+            } else if let Ok(value) = prop.as_u32() {
+                if value == 0 || value == 0xffff_ffff {
+                    errors.push(ValidationError::new(
+                        ValidationErrorKind::ReservedPhandle,
+                        path.to_string(),
+                        Severity::Error,
+                    ));
+                } else if let Some(existing) = phandles.insert(value, path.to_string())
+                    && existing != path
+                {
+                    // A node mirroring its own `phandle` onto `linux,phandle`
+                    // reinserts itself here and is not a real collision.
+                    errors.push(ValidationError::new(
+                        ValidationErrorKind::DuplicatePhandle(value),
+                        path.to_string(),
+                        Severity::Error,
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(prop) = node.property("interrupt-parent")
+        && let Ok(value) = prop.as_u32()
+    {
+        interrupt_parents.push((path.to_string(), value));
+    }
+
+    let mut seen_names = BTreeSet::new();
+    let mut child_has_reg = false;
+    for child in node.children() {
+        if !seen_names.insert(child.name()) {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::DuplicateSiblingName(child.name().to_string()),
+                path.to_string(),
+                Severity::Error,
+            ));
+        }
+        child_has_reg |= child.property("reg").is_some();
+    }
+    if child_has_reg
+        && (node.property("#address-cells").is_none() || node.property("#size-cells").is_none())
+    {
+        errors.push(ValidationError::new(
+            ValidationErrorKind::MissingAddressSizeCells,
+            path.to_string(),
+            Severity::Warning,
+        ));
+    }
+
+    let child_address_cells = node.address_cells().unwrap_or(2);
+    let child_size_cells = node.size_cells().unwrap_or(1);
+    for child in node.children() {
+        let child_path = join_path(path, child.name());
+        walk(
+            child,
+            &child_path,
+            child_address_cells,
+            child_size_cells,
+            phandles,
+            interrupt_parents,
+            errors,
+        );
+    }
+}
+
+fn check_unit_address(
+    node: &DeviceTreeNode,
+    path: &str,
+    address_cells: u32,
+    size_cells: u32,
+    errors: &mut Vec<ValidationError>,
+) {
+    let unit_address = node.name().split_once('@').map(|(_, unit)| unit);
+    let reg = node.property("reg");
+
+    match (unit_address, reg) {
+        (Some(unit), Some(reg)) => {
+            if let Ok(unit) = u64::from_str_radix(unit, 16)
+                && let Ok(mut reg) = reg.as_reg(address_cells, size_cells)
+                && let Some(first) = reg.next()
+                && first.0 != unit
+            {
+                errors.push(ValidationError::new(
+                    ValidationErrorKind::UnitAddressMismatch,
+                    path.to_string(),
+                    Severity::Warning,
+                ));
+            }
+        }
+        (Some(_), None) if node.property("ranges").is_none() => {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::MissingReg,
+                path.to_string(),
+                Severity::Warning,
+            ));
+        }
+        (None, Some(_)) => {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::MissingUnitAddress,
+                path.to_string(),
+                Severity::Warning,
+            ));
+        }
+        _ => {}
+    }
+}
+
+/// Checks that `node`'s `reg` property (addressed with `address_cells` +
+/// `size_cells`, inherited from its parent) and non-empty `ranges` property
+/// (whose entries are `node`'s own `#address-cells` + `address_cells` +
+/// `node`'s own `#size-cells` wide, since `ranges` maps `node`'s child bus
+/// onto its parent's) are both a whole number of entries long.
+fn check_reg_ranges_lengths(
+    node: &DeviceTreeNode,
+    path: &str,
+    address_cells: u32,
+    size_cells: u32,
+    errors: &mut Vec<ValidationError>,
+) {
+    if let Some(reg) = node.property("reg") {
+        let entry_len = (address_cells + size_cells) as usize * 4;
+        if entry_len == 0 || !reg.value().len().is_multiple_of(entry_len) {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::RegRangesCellMismatch,
+                path.to_string(),
+                Severity::Error,
+            ));
+        }
+    }
+
+    if let Some(ranges) = node.property("ranges")
+        && !ranges.value().is_empty()
+    {
+        let child_address_cells = node.address_cells().unwrap_or(2);
+        let child_size_cells = node.size_cells().unwrap_or(1);
+        let entry_len = (child_address_cells + address_cells + child_size_cells) as usize * 4;
+        if entry_len == 0 || !ranges.value().len().is_multiple_of(entry_len) {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::RegRangesCellMismatch,
+                path.to_string(),
+                Severity::Error,
+            ));
+        }
+    }
+}
+
+/// Returns whether `name` uses only the character set the devicetree
+/// specification allows: a `@unit-address` suffix of `[0-9a-zA-Z,._+-]`,
+/// appended to a base name from the same set.
+fn is_valid_name(name: &str) -> bool {
+    if name.is_empty() {
+        return true;
+    }
+    let is_valid_part = |s: &str| {
+        !s.is_empty()
+            && s.bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b',' | b'.' | b'_' | b'+' | b'-'))
+    };
+    match name.split_once('@') {
+        Some((base, unit)) => is_valid_part(base) && is_valid_part(unit),
+        None => is_valid_part(name),
+    }
+}
+
+/// Joins a node's own path with a child's name, avoiding a doubled `/` when
+/// `parent` is the root.
+fn join_path(parent: &str, name: &str) -> String {
+    if parent == "/" {
+        alloc::format!("/{name}")
+    } else {
+        alloc::format!("{parent}/{name}")
+    }
+}
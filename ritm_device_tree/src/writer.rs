@@ -24,8 +24,17 @@ const LAST_VERSION: u32 = 17;
 const LAST_COMP_VERSION: u32 = 16;
 
 pub(crate) fn to_bytes(tree: &DeviceTree) -> Vec<u8> {
-    let memory_reservations = write_memory_reservations(&tree.memory_reservations);
-    let (struct_block, strings_block) = write_root(tree.root());
+    to_bytes_from_parts(tree.root(), &tree.memory_reservations)
+}
+
+/// Serializes `root` into a flattened device tree blob, with the given
+/// memory reservations.
+pub(crate) fn to_bytes_from_parts(
+    root: &DeviceTreeNode,
+    memory_reservations: &[MemoryReservation],
+) -> Vec<u8> {
+    let memory_reservations = write_memory_reservations(memory_reservations);
+    let (struct_block, strings_block) = write_root(root);
 
     let off_mem_rsvmap = size_of::<FdtHeader>();
     let off_dt_struct = off_mem_rsvmap + memory_reservations.len();
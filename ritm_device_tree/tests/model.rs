@@ -8,7 +8,9 @@
 
 #![cfg(feature = "write")]
 
-use ritm_device_tree::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use ritm_device_tree::model::{
+    DeviceTree, DeviceTreeNode, DeviceTreeProperty, ValidationConstraints,
+};
 
 #[test]
 fn tree_creation() {
@@ -148,3 +150,33 @@ fn device_tree_format() {
 "#
     );
 }
+
+#[test]
+fn dts_round_trip() {
+    let tree = DeviceTree::new(
+        DeviceTreeNode::builder("")
+            .property(DeviceTreeProperty::new("compatible", "test"))
+            .property(DeviceTreeProperty::new("prop-u32", 1u32.to_be_bytes()))
+            .child(
+                DeviceTreeNode::builder("child-a")
+                    .property(DeviceTreeProperty::new("label", "hello\0"))
+                    .build(),
+            )
+            .build(),
+    );
+
+    let reparsed = DeviceTree::from_dts(&tree.to_string()).unwrap();
+
+    assert_eq!(tree, reparsed);
+}
+
+#[test]
+fn validate_accepts_empty_root_name() {
+    let tree = DeviceTree::new(
+        DeviceTreeNode::builder("")
+            .property(DeviceTreeProperty::new("compatible", "test"))
+            .build(),
+    );
+
+    assert!(tree.validate(&ValidationConstraints::default()).is_ok());
+}
@@ -8,7 +8,10 @@
 
 #![cfg(feature = "write")]
 
-use ritm_device_tree::{fdt::Fdt, model::DeviceTree};
+use ritm_device_tree::{
+    fdt::Fdt,
+    model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty},
+};
 
 #[test]
 fn test_apply_overlay() {
@@ -29,3 +32,34 @@ fn test_apply_overlay() {
 
     assert_eq!(base_tree, merged_tree);
 }
+
+#[test]
+fn test_apply_overlay_target_path() {
+    let mut base = DeviceTree::new(
+        DeviceTreeNode::builder("")
+            .child(
+                DeviceTreeNode::builder("soc").child(DeviceTreeNode::builder("uart").build()).build(),
+            )
+            .build(),
+    );
+
+    let overlay = DeviceTree::new(
+        DeviceTreeNode::builder("")
+            .child(
+                DeviceTreeNode::builder("fragment@0")
+                    .property(DeviceTreeProperty::from_string("target-path", "/soc/uart"))
+                    .child(
+                        DeviceTreeNode::builder("__overlay__")
+                            .property(DeviceTreeProperty::from_string("status", "okay"))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+
+    base.apply_overlay(&overlay).unwrap();
+
+    let uart = base.root().child("soc").unwrap().child("uart").unwrap();
+    assert_eq!(uart.property("status").unwrap().as_string().unwrap(), "okay");
+}
@@ -0,0 +1,65 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(all(feature = "write", feature = "serde"))]
+
+use ritm_device_tree::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+
+fn sample_tree() -> DeviceTree {
+    let mut tree = DeviceTree::new(
+        DeviceTreeNode::builder("")
+            .property(DeviceTreeProperty::new("compatible", "test"))
+            .property(DeviceTreeProperty::new("prop-u32", 1u32.to_be_bytes()))
+            .property(DeviceTreeProperty::new("prop-bytes", [0xffu8, 0x00, 0xab, 0xcd]))
+            .child(
+                DeviceTreeNode::builder("child-a")
+                    .property(DeviceTreeProperty::new("child-prop", "a"))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.add_reserved_region(0x1000, 0x2000);
+    tree
+}
+
+#[test]
+fn json_round_trip_preserves_tree() {
+    let tree = sample_tree();
+    let json = serde_json::to_string(&tree).unwrap();
+    let restored: DeviceTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(tree, restored);
+}
+
+#[test]
+fn json_round_trip_preserves_dtb_bytes() {
+    let tree = sample_tree();
+    let json = serde_json::to_string(&tree).unwrap();
+    let restored: DeviceTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(tree.to_dtb(), restored.to_dtb());
+}
+
+#[test]
+fn non_utf8_property_values_survive_json() {
+    let mut tree = DeviceTree::new(DeviceTreeNode::new(""));
+    tree.root_mut()
+        .add_property(DeviceTreeProperty::new("raw", vec![0xff, 0xfe, 0x00, 0x80]));
+    let json = serde_json::to_string(&tree).unwrap();
+    let restored: DeviceTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        restored.root().property("raw").unwrap().value(),
+        &[0xff, 0xfe, 0x00, 0x80]
+    );
+}
+
+#[test]
+fn json_snapshot_is_human_readable() {
+    let tree = sample_tree();
+    let json = serde_json::to_string(&tree).unwrap();
+    assert!(json.contains("\"compatible\""));
+    assert!(json.contains("\"child-a\""));
+}
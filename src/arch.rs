@@ -50,6 +50,24 @@ pub fn tlbi_alle2is() {
     }
 }
 
+/// Invalidate the EL2 stage-1 TLB entry translating `va`.
+pub fn tlbi_vae2is(va: u64) {
+    let arg = va >> 12;
+    // SAFETY: `tlbi vae2is` is always safe.
+    unsafe {
+        asm!("tlbi vae2is, {0}", in(reg) arg, options(nostack, preserves_flags));
+    }
+}
+
+/// Invalidate the EL1 stage-2 TLB entry translating `ipa`.
+pub fn tlbi_ipas2e1is(ipa: u64) {
+    let arg = ipa >> 12;
+    // SAFETY: `tlbi ipas2e1is` is always safe.
+    unsafe {
+        asm!("tlbi ipas2e1is, {0}", in(reg) arg, options(nostack, preserves_flags));
+    }
+}
+
 macro_rules! sys_reg {
     ($name:ident, {$($const_name:ident: $const_val:expr),*}) => {
         pub mod $name {
@@ -109,12 +127,51 @@ sys_reg!(sctlr_el2, {
 sys_reg!(clidr_el1);
 sys_reg!(csselr_el1);
 sys_reg!(ccsidr_el1);
-sys_reg!(hcr_el2);
+sys_reg!(hcr_el2, {
+    VM: 1 << 0,
+    IMO: 1 << 4,
+    TSC: 1 << 19,
+    RW: 1 << 31
+});
 sys_reg!(cntvoff_el2);
-sys_reg!(cnthctl_el2);
-sys_reg!(spsr_el2);
+sys_reg!(cnthctl_el2, {
+    ENABLE: 1 << 0,
+    IMASK: 1 << 1
+});
+sys_reg!(spsr_el2, {
+    EL1H: 0b0101,
+    MASK_ALL: 0b1111 << 6
+});
 sys_reg!(elr_el2);
 sys_reg!(sp_el1);
+sys_reg!(esr_el2);
+sys_reg!(far_el2);
+sys_reg!(hpfar_el2);
+sys_reg!(vtcr_el2);
+sys_reg!(vttbr_el2);
+sys_reg!(mpidr_el1);
+sys_reg!(ctr_el0);
+
+/// Returns the value of `ESR_EL2`, describing the cause of the most recent
+/// synchronous exception taken to EL2.
+pub fn esr() -> u64 {
+    // SAFETY: Reading `ESR_EL2` is always safe.
+    unsafe { esr_el2::read() }
+}
+
+/// Returns the value of `FAR_EL2`, the faulting virtual/intermediate
+/// physical address of the most recent synchronous exception.
+pub fn far() -> u64 {
+    // SAFETY: Reading `FAR_EL2` is always safe.
+    unsafe { far_el2::read() }
+}
+
+/// Returns the value of `HPFAR_EL2`, the faulting intermediate physical
+/// address (bits `[47:12]` in bits `[39:4]`) of a stage-2 translation fault.
+pub fn hpfar() -> u64 {
+    // SAFETY: Reading `HPFAR_EL2` is always safe.
+    unsafe { hpfar_el2::read() }
+}
 
 pub(super) fn disable_mmu_and_caches() {
     invalidate_dcache();
@@ -205,3 +262,101 @@ pub fn invalidate_dcache() {
     dsb();
     isb();
 }
+
+/// Returns the size in bytes of the smallest data cache line, read from the
+/// `DminLine` field of `CTR_EL0`.
+fn dcache_line_size() -> u64 {
+    // SAFETY: Reading `CTR_EL0` is always safe.
+    let ctr = unsafe { ctr_el0::read() };
+    4 << ((ctr >> 16) & 0xf)
+}
+
+/// Returns the size in bytes of the smallest instruction cache line, read
+/// from the `IminLine` field of `CTR_EL0`.
+fn icache_line_size() -> u64 {
+    // SAFETY: Reading `CTR_EL0` is always safe.
+    let ctr = unsafe { ctr_el0::read() };
+    4 << (ctr & 0xf)
+}
+
+/// Cleans the D-cache by VA to the point of coherency for every line
+/// overlapping `[start, end)`.
+pub fn dc_cvac(start: u64, end: u64) {
+    let line_size = dcache_line_size();
+    dsb();
+    let mut addr = start & !(line_size - 1);
+    while addr < end {
+        // SAFETY: `dc cvac` is always safe, assuming the address is mapped.
+        unsafe {
+            asm!("dc cvac, {0}", in(reg) addr, options(nostack, preserves_flags));
+        }
+        addr += line_size;
+    }
+    dsb();
+}
+
+/// Cleans and invalidates the D-cache by VA to the point of coherency for
+/// every line overlapping `[start, end)`.
+pub fn dc_civac(start: u64, end: u64) {
+    let line_size = dcache_line_size();
+    dsb();
+    let mut addr = start & !(line_size - 1);
+    while addr < end {
+        // SAFETY: `dc civac` is always safe, assuming the address is mapped.
+        unsafe {
+            asm!("dc civac, {0}", in(reg) addr, options(nostack, preserves_flags));
+        }
+        addr += line_size;
+    }
+    dsb();
+}
+
+/// Invalidates the D-cache by VA to the point of coherency for every line
+/// overlapping `[start, end)`, discarding any dirty data rather than writing
+/// it back.
+///
+/// # Safety
+///
+/// The caller must ensure that no dirty data in `[start, end)` needs to be
+/// preserved, since this discards it instead of writing it back; use
+/// [`dc_civac`] if it does.
+pub unsafe fn dc_ivac(start: u64, end: u64) {
+    let line_size = dcache_line_size();
+    dsb();
+    let mut addr = start & !(line_size - 1);
+    while addr < end {
+        // SAFETY: The caller guarantees it is safe to discard this range.
+        unsafe {
+            asm!("dc ivac, {0}", in(reg) addr, options(nostack, preserves_flags));
+        }
+        addr += line_size;
+    }
+    dsb();
+}
+
+/// Invalidates the I-cache by VA for every line overlapping `[start, end)`.
+fn ic_ivau(start: u64, end: u64) {
+    let line_size = icache_line_size();
+    let mut addr = start & !(line_size - 1);
+    while addr < end {
+        // SAFETY: `ic ivau` is always safe, assuming the address is mapped.
+        unsafe {
+            asm!("ic ivau, {0}", in(reg) addr, options(nostack, preserves_flags));
+        }
+        addr += line_size;
+    }
+}
+
+/// Cleans `[start, end)` to the point of coherency, then invalidates the
+/// instruction cache for the same range.
+///
+/// Call this after writing code into guest memory (e.g. staging a loaded
+/// kernel image) so it executes correctly, without having to invalidate the
+/// entire cache hierarchy.
+pub fn sync_icache_range(start: u64, end: u64) {
+    dc_cvac(start, end);
+    dsb();
+    ic_ivau(start, end);
+    dsb();
+    isb();
+}
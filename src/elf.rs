@@ -0,0 +1,208 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An ELF64 loader for staging a guest kernel image into its allocated
+//! memory before boot.
+
+/// Identifies which program-header address field a [`load`] call should use
+/// as the destination for each segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentAddress {
+    /// Use `p_paddr`, for bare-metal guests that run with the stage-1 MMU
+    /// off (or identity-mapped) at entry.
+    Physical,
+    /// Use `p_vaddr`, for guests that expect their own translation to
+    /// already be active at entry.
+    Virtual,
+}
+
+/// An error encountered while loading an ELF image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfLoadError {
+    /// The image is too short to contain the header it claims to.
+    Truncated,
+    /// `e_ident`'s magic bytes are not `\x7fELF`.
+    BadMagic,
+    /// The image is not `ELFCLASS64`.
+    NotElf64,
+    /// The image is not little-endian.
+    NotLittleEndian,
+    /// `e_machine` is not `EM_AARCH64`.
+    WrongMachine,
+    /// A `PT_LOAD` segment's `p_align` is not a power of two.
+    BadAlignment,
+    /// A `PT_LOAD` segment's `p_vaddr` is not congruent to
+    /// `p_offset` modulo `p_align`, as the ELF spec requires.
+    MisalignedSegment,
+    /// A `PT_LOAD` segment's destination falls outside the guest's
+    /// allocated memory region.
+    SegmentOutOfBounds,
+}
+
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EM_AARCH64: u16 = 183;
+const PT_LOAD: u32 = 1;
+
+const EHDR_SIZE: usize = 64;
+const PHDR_SIZE: usize = 56;
+
+struct ElfHeader {
+    e_entry: u64,
+    e_phoff: u64,
+    e_phentsize: u16,
+    e_phnum: u16,
+}
+
+impl ElfHeader {
+    fn parse(image: &[u8]) -> Result<Self, ElfLoadError> {
+        let header = image.get(..EHDR_SIZE).ok_or(ElfLoadError::Truncated)?;
+
+        if header[..ELF_MAGIC.len()] != ELF_MAGIC {
+            return Err(ElfLoadError::BadMagic);
+        }
+        if header[4] != ELFCLASS64 {
+            return Err(ElfLoadError::NotElf64);
+        }
+        if header[5] != ELFDATA2LSB {
+            return Err(ElfLoadError::NotLittleEndian);
+        }
+
+        let e_machine = read_u16(header, 18);
+        if e_machine != EM_AARCH64 {
+            return Err(ElfLoadError::WrongMachine);
+        }
+
+        Ok(Self {
+            e_entry: read_u64(header, 24),
+            e_phoff: read_u64(header, 32),
+            e_phentsize: read_u16(header, 54),
+            e_phnum: read_u16(header, 56),
+        })
+    }
+}
+
+struct ProgramHeader {
+    p_type: u32,
+    p_offset: u64,
+    p_vaddr: u64,
+    p_paddr: u64,
+    p_filesz: u64,
+    p_memsz: u64,
+    p_align: u64,
+}
+
+impl ProgramHeader {
+    fn parse(image: &[u8], offset: u64) -> Result<Self, ElfLoadError> {
+        let offset = usize::try_from(offset).map_err(|_err| ElfLoadError::Truncated)?;
+        let header = image
+            .get(offset..offset + PHDR_SIZE)
+            .ok_or(ElfLoadError::Truncated)?;
+
+        Ok(Self {
+            p_type: read_u32(header, 0),
+            p_offset: read_u64(header, 8),
+            p_vaddr: read_u64(header, 16),
+            p_paddr: read_u64(header, 24),
+            p_filesz: read_u64(header, 32),
+            p_memsz: read_u64(header, 40),
+            p_align: read_u64(header, 48),
+        })
+    }
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(buf[offset..offset + 2].try_into().expect("slice has 2 elements"))
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().expect("slice has 4 elements"))
+}
+
+fn read_u64(buf: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(buf[offset..offset + 8].try_into().expect("slice has 8 elements"))
+}
+
+/// Loads an ELF64 AArch64 image's `PT_LOAD` segments into `guest_region`,
+/// and returns `e_entry`, the address the caller should seed `elr_el2`
+/// with.
+///
+/// `guest_region` is the guest's allocated memory, and `guest_region_base`
+/// is the address (physical or virtual, per `address_mode`) that
+/// `guest_region[0]` corresponds to. Each segment's `p_filesz` bytes are
+/// copied from `image` to its destination, and the remaining
+/// `p_memsz - p_filesz` bytes (BSS) are zero-filled.
+///
+/// # Errors
+///
+/// Returns an error if `e_ident`'s magic, class, or endianness don't match
+/// a little-endian ELF64 image, if `e_machine` isn't `EM_AARCH64`, if the
+/// image is too short to contain a header or program header it claims to,
+/// or if a `PT_LOAD` segment's destination (or alignment) is invalid, or
+/// falls outside `guest_region`.
+pub fn load(
+    image: &[u8],
+    guest_region: &mut [u8],
+    guest_region_base: u64,
+    address_mode: SegmentAddress,
+) -> Result<u64, ElfLoadError> {
+    let header = ElfHeader::parse(image)?;
+    let phentsize = u64::from(header.e_phentsize);
+
+    for i in 0..u64::from(header.e_phnum) {
+        let phdr_offset = header
+            .e_phoff
+            .checked_add(i * phentsize)
+            .ok_or(ElfLoadError::Truncated)?;
+        let phdr = ProgramHeader::parse(image, phdr_offset)?;
+
+        if phdr.p_type != PT_LOAD {
+            continue;
+        }
+
+        if phdr.p_align > 1 {
+            if !phdr.p_align.is_power_of_two() {
+                return Err(ElfLoadError::BadAlignment);
+            }
+            if (phdr.p_vaddr % phdr.p_align) != (phdr.p_offset % phdr.p_align) {
+                return Err(ElfLoadError::MisalignedSegment);
+            }
+        }
+
+        if phdr.p_filesz > phdr.p_memsz {
+            return Err(ElfLoadError::SegmentOutOfBounds);
+        }
+
+        let dest_addr = match address_mode {
+            SegmentAddress::Physical => phdr.p_paddr,
+            SegmentAddress::Virtual => phdr.p_vaddr,
+        };
+        let dest_offset = dest_addr
+            .checked_sub(guest_region_base)
+            .ok_or(ElfLoadError::SegmentOutOfBounds)?;
+        let dest_end = dest_offset
+            .checked_add(phdr.p_memsz)
+            .ok_or(ElfLoadError::SegmentOutOfBounds)?;
+        if dest_end > guest_region.len() as u64 {
+            return Err(ElfLoadError::SegmentOutOfBounds);
+        }
+
+        let dest_offset = dest_offset as usize;
+        let file_size = phdr.p_filesz as usize;
+        let mem_size = phdr.p_memsz as usize;
+        let file_start = phdr.p_offset as usize;
+        let file_end = file_start.checked_add(file_size).ok_or(ElfLoadError::Truncated)?;
+        let src = image.get(file_start..file_end).ok_or(ElfLoadError::Truncated)?;
+
+        guest_region[dest_offset..dest_offset + file_size].copy_from_slice(src);
+        guest_region[dest_offset + file_size..dest_offset + mem_size].fill(0);
+    }
+
+    Ok(header.e_entry)
+}
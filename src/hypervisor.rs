@@ -15,8 +15,9 @@ use spin::mutex::SpinMutex;
 
 use crate::{
     arch::{self, esr, far},
-    platform::{Platform, PlatformImpl},
+    platform::{Platform, PlatformImpl, SystemEvent},
     simple_map::SimpleMap,
+    stage2::Stage2PageTable,
 };
 
 /// Entry point for EL1 execution.
@@ -73,6 +74,16 @@ pub unsafe fn entry_point_el1(arg0: u64, arg1: u64, arg2: u64, arg3: u64, entry_
         arch::elr_el2::write(entry_point);
     }
 
+    // Confine the guest to its stage-2-mapped memory, if a table has been
+    // installed via `set_stage2_page_table`.
+    // SAFETY: We are running at EL2, about to `eret` to the EL1 guest this
+    // table is meant to confine.
+    unsafe {
+        if let Some(stage2) = STAGE2_PAGE_TABLE.lock().as_ref() {
+            stage2.activate();
+        }
+    }
+
     // SAFETY: The caller ensures that the provided arguments are valid and that this is called
     // from EL2. We've set the `elr_el2` system register right before calling this, and the caller
     // ensured that the value we've set is a valid address for EL1 execution that never returns.
@@ -103,6 +114,21 @@ pub unsafe extern "C" fn eret_to_el1(x0: u64, x1: u64, x2: u64, x3: u64) -> ! {
     );
 }
 
+/// Installs the stage-2 page table used to confine the EL1 guest.
+///
+/// The table takes effect the next time `entry_point_el1` runs, i.e. the
+/// next time a core (re)enters the guest, whether on initial boot, PSCI
+/// `CPU_ON`, or resume from suspend.
+///
+/// No caller in `main.rs` invokes this yet, since the boot path there stops
+/// before loading and entering a guest; until something does, the guest
+/// stays unconfined. See the module docs on [`crate::stage2`].
+pub fn set_stage2_page_table(table: Stage2PageTable) {
+    *STAGE2_PAGE_TABLE.lock() = Some(table);
+}
+
+static STAGE2_PAGE_TABLE: SpinMutex<Option<Stage2PageTable>> = SpinMutex::new(None);
+
 pub fn handle_sync_lower(mut register_state: RegisterStateRef) {
     let esr = esr();
     let ec = u8::try_from((esr >> 26) & 0x3f).expect("`& 0x3f` guarantees the value fits in u8");
@@ -114,14 +140,16 @@ pub fn handle_sync_lower(mut register_state: RegisterStateRef) {
 
             match function_id {
                 0x8400_0000..=0x8400_001F | 0xC400_0000..=0xC400_001F => {
-                    try_handle_psci(&mut register_state)
-                        .expect("Unknown PSCI call: {register_state:?}");
+                    handle_psci_call(&mut register_state);
                 }
                 _ => {
                     panic!("Unknown HVC/SMC call: {register_state:?}");
                 }
             }
         }
+        ExceptionClass::InstructionAbortLowerEl | ExceptionClass::DataAbortLowerEl => {
+            handle_stage2_abort(&ec, &mut register_state);
+        }
         ExceptionClass::Unknown(_) => {
             panic!(
                 "Unexpected sync_lower, far={:#x}, register_state={register_state:?}",
@@ -131,17 +159,53 @@ pub fn handle_sync_lower(mut register_state: RegisterStateRef) {
     }
 }
 
+/// Handles a stage-2 abort taken because the guest accessed an IPA that the
+/// active [`Stage2PageTable`] leaves unmapped (typically an MMIO window the
+/// hypervisor wants to trap rather than pass through).
+///
+/// This is the extension point for emulating a trapped device access or
+/// forwarding it elsewhere; it currently only reports the fault, since no
+/// device model exists yet.
+fn handle_stage2_abort(ec: &ExceptionClass, register_state: &mut RegisterStateRef) {
+    // `HPFAR_EL2.FIPA` holds IPA bits `[47:12]` in register bits `[39:4]`
+    // for a stage-2 abort, so masking off the low 4 reserved bits and
+    // shifting left by 8 (instead of right by 4, then left by 12)
+    // reconstructs the faulting IPA. `FAR_EL2` only holds a faulting VA,
+    // which is not meaningful here unless stage-1 translation was also
+    // enabled in the guest.
+    let hpfar_fipa_mask: u64 = ((1 << 40) - 1) & !0xf;
+    let ipa = (arch::hpfar() & hpfar_fipa_mask) << 8;
+    panic!(
+        "Unhandled stage-2 abort ({ec:?}), ipa={ipa:#x}, far={:#x}, register_state={register_state:?}",
+        far(),
+    );
+}
+
 const AARCH64_INSTRUCTION_LENGTH: usize = 4;
 
-fn try_handle_psci(register_state: &mut RegisterStateRef) -> Result<(), arm_psci::Error> {
+/// Decodes and dispatches a trapped PSCI call, writing its result back into
+/// the guest's `x0` and skipping the `HVC`/`SMC` instruction that trapped.
+///
+/// A function ID this backend doesn't recognise (as opposed to one it
+/// recognises but declines to emulate, which already returns
+/// `NOT_SUPPORTED` from [`handle_psci`]) also yields `NOT_SUPPORTED`, since
+/// an unmodified guest kernel is expected to probe for PSCI functions this
+/// way rather than treating an unknown one as fatal.
+fn handle_psci_call(register_state: &mut RegisterStateRef) {
     let [fn_id, arg0, arg1, arg2, ..] = register_state.registers;
     debug!(
-        "Forwarding the PSCI call: fn_id={fn_id:#x}, arg0={arg0:#x}, arg1={arg1:#x}, arg2={arg2:#x}"
+        "Handling the PSCI call: fn_id={fn_id:#x}, arg0={arg0:#x}, arg1={arg1:#x}, arg2={arg2:#x}"
     );
 
     // SAFETY: We are handling a trapped HVC or SMC instruction, which is likely a PSCI call.
     // The arguments are passed from the guest.
-    let out = unsafe { handle_psci(fn_id, arg0, arg1, arg2)? };
+    let out = match unsafe { handle_psci(fn_id, arg0, arg1, arg2) } {
+        Ok(out) => out,
+        Err(error) => {
+            debug!("Unrecognised PSCI call {fn_id:#x}: {error:?}");
+            psci_not_supported()
+        }
+    };
     debug!("PSCI call output: out={out:#x}");
 
     // SAFETY: This is an answer to the guest calling HVC/SMC, so it expects x0..3 will
@@ -155,8 +219,6 @@ fn try_handle_psci(register_state: &mut RegisterStateRef) -> Result<(), arm_psci
         regs.registers[3] = 0;
         regs.elr += AARCH64_INSTRUCTION_LENGTH; // move to the next instruction to avoid looping
     }
-
-    Ok(())
 }
 
 /// Handles a PSCI call.
@@ -175,38 +237,32 @@ unsafe fn handle_psci(fn_id: u64, arg0: u64, arg1: u64, arg2: u64) -> Result<u64
 
     let psci_fn = arm_psci::Function::try_from(&[fn_id, arg0, arg1, arg2])?;
     match psci_fn {
-        Version
-        | CpuOff
-        | AffinityInfo { .. }
-        | Migrate { .. }
-        | MigrateInfoType
+        Version => Ok(PSCI_VERSION),
+        CpuOff => {
+            set_vcpu_state(current_mpidr(), VcpuPowerState::Off);
+            // A core that has called `CPU_OFF` never returns.
+            halt_core();
+        }
+        AffinityInfo { target_affinity, .. } => Ok(affinity_info(target_affinity.into())),
+        MigrateInfoType => Ok(MIGRATE_INFO_TYPE_NOT_PRESENT),
+        SystemOff => forward_system_event(SystemEvent::Off, fn_id, arg0, arg1, arg2),
+        SystemReset => forward_system_event(SystemEvent::Reset, fn_id, arg0, arg1, arg2),
+        SystemReset2 { .. } => {
+            forward_system_event(SystemEvent::Reset2, fn_id, arg0, arg1, arg2)
+        }
+        Features { .. } => Ok(psci_features(arg0)),
+        Migrate { .. }
         | MigrateInfoUpCpu { .. }
-        | SystemOff
         | SystemOff2 { .. }
-        | SystemReset
-        | SystemReset2 { .. }
         | MemProtect { .. }
         | MemProtectCheckRange { .. }
-        | Features { .. }
         | CpuFreeze
         | CpuDefaultSuspend { .. }
         | NodeHwState { .. }
         | SystemSuspend { .. }
         | SetSuspendMode { .. }
         | StatResidency { .. }
-        | StatCount { .. } => {
-            // forward the PSCI call
-            let mut smc_args = [0; 17];
-            smc_args[0] = arg0;
-            smc_args[1] = arg1;
-            smc_args[2] = arg2;
-            #[expect(
-                clippy::cast_possible_truncation,
-                reason = "the fn_id is a u32 per specification, so can be truncated"
-            )]
-            let result = smccc::smc64(fn_id as u32, smc_args);
-            Ok(result[0])
-        }
+        | StatCount { .. } => Ok(psci_not_supported()),
         CpuOn { target_cpu, entry } => {
             let result = psci_cpu_on(fn_id, target_cpu, entry);
             Ok(u64::from(i32::from(result).cast_unsigned()))
@@ -218,6 +274,149 @@ unsafe fn handle_psci(fn_id: u64, arg0: u64, arg1: u64, arg2: u64) -> Result<u64
     }
 }
 
+/// `PSCI_VERSION`'s return value: major version 1, minor version 1.
+const PSCI_VERSION: u64 = (1 << 16) | 1;
+/// `MIGRATE_INFO_TYPE`'s return value meaning no Trusted OS is present, so
+/// migration is not a concern.
+const MIGRATE_INFO_TYPE_NOT_PRESENT: u64 = 2;
+
+/// The `NOT_SUPPORTED` PSCI return code, converted the same way the
+/// successful call sites in this file convert `arm_psci::ReturnCode`.
+fn psci_not_supported() -> u64 {
+    u64::from(i32::from(arm_psci::ReturnCode::NotSupported).cast_unsigned())
+}
+
+/// The `DENIED` PSCI return code.
+fn psci_denied() -> u64 {
+    u64::from(i32::from(arm_psci::ReturnCode::Denied).cast_unsigned())
+}
+
+/// The PSCI function IDs (32- and 64-bit calling convention, where they
+/// differ) that this emulated backend answers, used to build the
+/// `PSCI_FEATURES` response.
+const EMULATED_FUNCTION_IDS: &[u64] = &[
+    0x8400_0000, // PSCI_VERSION
+    0x8400_0001,
+    0xC400_0001, // CPU_SUSPEND
+    0x8400_0002, // CPU_OFF
+    0x8400_0003,
+    0xC400_0003, // CPU_ON
+    0x8400_0004,
+    0xC400_0004, // AFFINITY_INFO
+    0x8400_0006, // MIGRATE_INFO_TYPE
+    0x8400_0008, // SYSTEM_OFF
+    0x8400_0009, // SYSTEM_RESET
+    0x8400_0012,
+    0xC400_0012, // SYSTEM_RESET2
+    0x8400_000A, // PSCI_FEATURES
+];
+
+/// Answers a `PSCI_FEATURES` query: `SUCCESS` (with no feature flags) for
+/// every function this backend emulates, `NOT_SUPPORTED` for anything else,
+/// since we no longer blindly forward unrecognized calls to the secure
+/// monitor.
+fn psci_features(queried_function_id: u64) -> u64 {
+    if EMULATED_FUNCTION_IDS.contains(&queried_function_id) {
+        0
+    } else {
+        psci_not_supported()
+    }
+}
+
+/// Returns the `AFFINITY_INFO` state for `target_affinity`, per this
+/// hypervisor's tracked [`VcpuPowerState`]. A suspended core is still
+/// reported as `0` (ON); PSCI only distinguishes suspended state through
+/// `CPU_SUSPEND`'s own return value, not `AFFINITY_INFO`.
+fn affinity_info(target_affinity: u64) -> u64 {
+    match vcpu_state(target_affinity) {
+        VcpuPowerState::On | VcpuPowerState::Suspended => 0,
+        VcpuPowerState::Off => 1,
+        VcpuPowerState::OnPending => 2,
+    }
+}
+
+/// Asks the platform whether to forward a guest-initiated `SYSTEM_OFF`,
+/// `SYSTEM_RESET`, or `SYSTEM_RESET2` to the real secure monitor, giving it
+/// a chance to log, snapshot, or veto the request first.
+///
+/// # Safety
+///
+/// Forwarding a system event via SMC is only as safe as the underlying SMC
+/// call; the caller must ensure this is called while handling a trapped
+/// guest HVC/SMC, as the other PSCI call sites do.
+unsafe fn forward_system_event(
+    event: SystemEvent,
+    fn_id: u64,
+    arg0: u64,
+    arg1: u64,
+    arg2: u64,
+) -> Result<u64, arm_psci::Error> {
+    if !PlatformImpl::on_system_event(event) {
+        return Ok(psci_denied());
+    }
+
+    let mut smc_args = [0; 17];
+    smc_args[0] = arg0;
+    smc_args[1] = arg1;
+    smc_args[2] = arg2;
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "the fn_id is a u32 per specification, so can be truncated"
+    )]
+    let result = smccc::smc64(fn_id as u32, smc_args);
+    // A successful SYSTEM_OFF/SYSTEM_RESET never returns; if we get here,
+    // the secure monitor rejected the request.
+    Ok(result[0])
+}
+
+/// Spins forever on the calling core, for a vCPU that has called `CPU_OFF`
+/// and must never return to the guest.
+fn halt_core() -> ! {
+    loop {
+        // SAFETY: `wfi` is always safe; it just waits for an interrupt.
+        unsafe {
+            core::arch::asm!("wfi", options(nostack, preserves_flags));
+        }
+    }
+}
+
+/// Returns the current core's `MPIDR_EL1` affinity bits.
+fn current_mpidr() -> u64 {
+    // SAFETY: Reading `MPIDR_EL1` is always safe.
+    unsafe { arch::mpidr_el1::read() }
+}
+
+/// The power state of a single vCPU, as tracked by the emulated PSCI
+/// backend, keyed by MPIDR in [`VCPU_STATES`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VcpuPowerState {
+    /// The vCPU has not been started, or has called `CPU_OFF`.
+    Off,
+    /// `CPU_ON` has been issued but the core has not yet resumed execution.
+    OnPending,
+    /// The vCPU is executing (or suspended; see [`affinity_info`]).
+    On,
+    /// The vCPU called `CPU_SUSPEND` and has not yet resumed.
+    Suspended,
+}
+
+static VCPU_STATES: SpinMutex<SimpleMap<u64, VcpuPowerState, MAX_CORES>> =
+    SpinMutex::new(SimpleMap::new());
+
+/// Returns the tracked power state of the vCPU identified by `mpidr`, or
+/// [`VcpuPowerState::Off`] if it has never been recorded (i.e. never
+/// `CPU_ON`'d).
+fn vcpu_state(mpidr: u64) -> VcpuPowerState {
+    match VCPU_STATES.lock().get_mut(&mpidr) {
+        Some(state) => *state,
+        None => VcpuPowerState::Off,
+    }
+}
+
+fn set_vcpu_state(mpidr: u64, state: VcpuPowerState) {
+    VCPU_STATES.lock().insert(mpidr, state);
+}
+
 fn psci_cpu_on(
     fn_id: u64,
     mpidr: arm_psci::Mpidr,
@@ -226,6 +425,8 @@ fn psci_cpu_on(
     let mpidr: u64 = mpidr.into();
     let stack = get_secondary_stack(mpidr);
 
+    set_vcpu_state(mpidr, VcpuPowerState::OnPending);
+
     // SAFETY: aarch64_rt::start_core is safe to call with a valid stack.
     unsafe {
         aarch64_rt::start_core::<smccc::Smc, _, _>(mpidr, stack, move || {
@@ -233,6 +434,7 @@ fn psci_cpu_on(
             let arg = entry.context_id();
             debug!("Started core with fn_id={fn_id:#x}, mpidr={mpidr:#x}, entry_ptr={entry_ptr:#x}, arg={arg}");
 
+            set_vcpu_state(mpidr, VcpuPowerState::On);
             entry_point_el1(arg, 0, 0, 0, entry_ptr);
         }).expect("Failed to start core");
     }
@@ -241,8 +443,7 @@ fn psci_cpu_on(
 }
 
 fn psci_cpu_suspend(power_state: arm_psci::PowerState, entry: arm_psci::EntryPoint) -> u64 {
-    // SAFETY: Reading MPIDR_EL1 is safe.
-    let mpidr = arch::mpidr_el1::read();
+    let mpidr = current_mpidr();
     let context = SuspendContext {
         stack_ptr: get_secondary_stack(mpidr).wrapping_add(1) as usize as u64,
         entry: restore_from_suspend,
@@ -255,6 +456,7 @@ fn psci_cpu_suspend(power_state: arm_psci::PowerState, entry: arm_psci::EntryPoi
 
     let context_ptr = core::ptr::from_mut(SUSPEND_CONTEXTS.lock().insert(mpidr, context));
 
+    set_vcpu_state(mpidr, VcpuPowerState::Suspended);
     let result = smccc::psci::cpu_suspend::<smccc::Smc>(
         power_state.into(),
         warm_boot_entry::<SuspendCoreData> as usize as u64,
@@ -263,6 +465,7 @@ fn psci_cpu_suspend(power_state: arm_psci::PowerState, entry: arm_psci::EntryPoi
 
     // If we return here, the suspend failed or was not a power down.
     SUSPEND_CONTEXTS.lock().remove(&mpidr);
+    set_vcpu_state(mpidr, VcpuPowerState::On);
 
     match result {
         Ok(()) => u64::from(i32::from(arm_psci::ReturnCode::Success).cast_unsigned()),
@@ -292,6 +495,7 @@ extern "C" fn restore_from_suspend(context: &mut SuspendContext<SuspendCoreData>
         "Restoring from suspend: entry={:#x}, ctx={:#x}",
         context.data.entry_point, context.data.context_id
     );
+    set_vcpu_state(context.data.mpidr, VcpuPowerState::On);
 
     // SAFETY: We are restoring the execution of the guest, assuming the entry point and
     // context_id we saved earlier from the guest is valid.
@@ -307,10 +511,16 @@ static SUSPEND_CONTEXTS: SpinMutex<SimpleMap<u64, SuspendContext<SuspendCoreData
 /// The class of an exception.
 #[derive(Debug)]
 enum ExceptionClass {
+    /// Instruction Abort taken from a lower Exception Level, i.e. a stage-2
+    /// fault on an instruction fetch.
+    InstructionAbortLowerEl,
     /// HVC instruction execution in `AArch64` state.
     HvcTrappedInAArch64,
     /// SMC instruction execution in `AArch64` state.
     SmcTrappedInAArch64,
+    /// Data Abort taken from a lower Exception Level, i.e. a stage-2 fault
+    /// on a data access.
+    DataAbortLowerEl,
     #[allow(unused)]
     /// Unknown exception class.
     Unknown(u8),
@@ -319,8 +529,10 @@ enum ExceptionClass {
 impl ExceptionClass {
     fn new(value: u8) -> Self {
         match value {
+            0x20 => Self::InstructionAbortLowerEl,
             0x16 => Self::HvcTrappedInAArch64,
             0x17 => Self::SmcTrappedInAArch64,
+            0x24 => Self::DataAbortLowerEl,
             _ => Self::Unknown(value),
         }
     }
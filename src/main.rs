@@ -13,25 +13,38 @@
 
 extern crate alloc;
 
+mod arch;
 mod console;
+mod elf;
 mod exceptions;
+mod hypervisor;
 mod logger;
+mod memory;
 mod pagetable;
 mod platform;
+mod simple_map;
+mod stage2;
 
+use aarch64_paging::idmap::IdMap;
 use aarch64_paging::paging::PAGE_SIZE;
 use aarch64_rt::entry;
 use buddy_system_allocator::{Heap, LockedHeap};
 use core::ops::DerefMut;
 use log::{LevelFilter, info};
+use ritm_device_tree::fdt::Fdt;
+use ritm_device_tree::model::DeviceTree;
 use spin::mutex::{SpinMutex, SpinMutexGuard};
 
+use crate::memory::MemoryLayout;
 use crate::platform::{Platform, PlatformImpl};
 
 const LOG_LEVEL: LevelFilter = LevelFilter::Info;
 
-const HEAP_SIZE: usize = 40 * PAGE_SIZE;
-static HEAP: SpinMutex<[u8; HEAP_SIZE]> = SpinMutex::new([0; HEAP_SIZE]);
+/// Size of the small static heap used to get the allocator working before
+/// the real memory layout has been discovered from the device tree (parsing
+/// it already needs `alloc`).
+const BOOTSTRAP_HEAP_SIZE: usize = 4 * PAGE_SIZE;
+static BOOTSTRAP_HEAP: SpinMutex<[u8; BOOTSTRAP_HEAP_SIZE]> = SpinMutex::new([0; BOOTSTRAP_HEAP_SIZE]);
 
 #[global_allocator]
 static HEAP_ALLOCATOR: LockedHeap<32> = LockedHeap::new();
@@ -48,20 +61,66 @@ fn main(x0: u64, x1: u64, x2: u64, x3: u64) -> ! {
     info!("starting ritm");
     info!("main({x0:#x}, {x1:#x}, {x2:#x}, {x3:#x})");
 
-    // Give the allocator some memory to allocate.
-    add_to_heap(
+    // Give the allocator a small bootstrap heap so we can parse the device
+    // tree, which the real, discovered heap region comes from.
+    init_heap(
         HEAP_ALLOCATOR.lock().deref_mut(),
-        SpinMutexGuard::leak(HEAP.try_lock().expect("failed to lock heap")).as_mut_slice(),
+        SpinMutexGuard::leak(BOOTSTRAP_HEAP.try_lock().expect("failed to lock heap")).as_mut_slice(),
     );
 
+    // SAFETY: The boot loader passes the physical address of a valid
+    // flattened device tree blob in x0, which is not otherwise aliased
+    // before this point.
+    let fdt = unsafe { Fdt::from_raw(x0 as *const u8) }.expect("failed to parse device tree");
+    let tree = DeviceTree::from_fdt(&fdt).expect("failed to build device tree model");
+    let memory_layout = MemoryLayout::from_device_tree(&tree).expect("failed to discover memory layout");
+
+    let mut idmap = IdMap::new(0, 1);
+    memory_layout.map_into(&mut idmap).expect("failed to map discovered memory layout");
+    // SAFETY: `idmap` maps at least everything the initial idmap did (all
+    // memory described by the device tree), and we are still running
+    // identity-mapped code/data, so switching to it doesn't invalidate any
+    // address we're currently using.
+    unsafe {
+        idmap.activate();
+    }
+
+    let (heap_start, heap_end) = memory_layout
+        .usable_heap_region()
+        .expect("no usable memory region found for the heap");
+    // SAFETY: `heap_start..heap_end` was just mapped with normal memory
+    // attributes by `idmap`, and `usable_heap_region` excludes every range
+    // reserved by the device tree, so it isn't used by anything else.
+    unsafe {
+        extend_heap(HEAP_ALLOCATOR.lock().deref_mut(), heap_start as usize, heap_end as usize);
+    }
+
+    // TODO: Load the guest kernel image, build a `Stage2PageTable` from the
+    // device tree's `/memory` regions, install it with
+    // `hypervisor::set_stage2_page_table`, and enter the guest via
+    // `hypervisor::entry_point_el1`. Until this exists, guests are not
+    // stage-2 confined; see the module docs on `stage2`.
     todo!();
 }
 
-/// Adds the given memory range to the given heap.
-fn add_to_heap<const ORDER: usize>(heap: &mut Heap<ORDER>, range: &'static mut [u8]) {
+/// Initializes `heap` with the given memory range.
+fn init_heap<const ORDER: usize>(heap: &mut Heap<ORDER>, range: &'static mut [u8]) {
     // SAFETY: The range we pass is valid because it comes from a mutable static reference, which it
     // effectively takes ownership of.
     unsafe {
         heap.init(range.as_mut_ptr() as usize, range.len());
     }
 }
+
+/// Grows `heap` with the physical address range `start..end`.
+///
+/// # Safety
+///
+/// `start..end` must be valid, mapped, and not otherwise in use for as long
+/// as the allocator may hand out memory from it.
+unsafe fn extend_heap<const ORDER: usize>(heap: &mut Heap<ORDER>, start: usize, end: usize) {
+    // SAFETY: The caller guarantees `start..end` is valid and unused.
+    unsafe {
+        heap.add_to_heap(start, end);
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The runtime memory layout, discovered from the device tree the boot
+//! loader hands to [`crate::main`].
+//!
+//! `Qemu::initial_idmap` maps the three 1 GiB blocks QEMU's virt machine
+//! happens to use before any Rust code runs, but a different board's
+//! `/memory` and `/reserved-memory` may carve up physical address space
+//! completely differently. [`MemoryLayout::from_device_tree`] reads the
+//! actual layout out of the parsed device tree, and [`MemoryLayout::map_into`]
+//! programs it into the real, dynamic page table that replaces the initial
+//! one once `main` has parsed the tree.
+
+use alloc::vec::Vec;
+
+use aarch64_paging::MapError;
+use aarch64_paging::idmap::IdMap;
+use aarch64_paging::paging::MemoryRegion;
+use ritm_device_tree::model::{DeviceTree, DeviceTreeNode};
+
+use crate::pagetable::{DEVICE_ATTRIBUTES, MEMORY_ATTRIBUTES};
+
+/// How a [`MemoryRange`] should be mapped, and whether it may be handed to
+/// the heap allocator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryKind {
+    /// Ordinary cacheable RAM from `/memory`, not carved out by any
+    /// `/reserved-memory` child: available to the heap.
+    Normal,
+    /// A `/reserved-memory` child with a `no-map` property: mapped
+    /// non-cacheable, since the device tree asks that no normal mapping be
+    /// created for it.
+    Device,
+    /// A `/reserved-memory` child without a `no-map` property: ordinary
+    /// cacheable RAM, but carved out of the usable pool (e.g. a firmware
+    /// buffer) and never handed to the heap.
+    Reserved,
+}
+
+/// A physical address range discovered from `/memory` or
+/// `/reserved-memory`, and how it should be mapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRange {
+    /// The first byte of the range.
+    pub start: u64,
+    /// One past the last byte of the range.
+    pub end: u64,
+    /// How this range should be mapped.
+    pub kind: MemoryKind,
+}
+
+/// An error discovering the memory layout from a device tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MemoryLayoutError {
+    /// The tree has no `/memory` node, or its `reg` property is missing or
+    /// malformed.
+    MissingMemory,
+    /// A `/reserved-memory` child has no `reg` property, or it is
+    /// malformed.
+    MalformedReservation,
+    /// A `/reserved-memory` child's range is not fully contained within a
+    /// single `/memory` range.
+    ReservationOutsideRam,
+    /// Two ranges from `/memory` or two ranges from `/reserved-memory`
+    /// overlap.
+    Overlap,
+}
+
+/// The memory layout discovered from a device tree: the usable RAM extent
+/// and the reservations carved out of it.
+#[derive(Debug, Clone)]
+pub struct MemoryLayout {
+    ranges: Vec<MemoryRange>,
+}
+
+impl MemoryLayout {
+    /// Discovers the memory layout from `tree`'s `/memory` and
+    /// `/reserved-memory` nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/memory` is missing or malformed, if a
+    /// `/reserved-memory` child is malformed or falls outside every
+    /// `/memory` range, or if any two ranges of the same kind overlap.
+    pub fn from_device_tree(tree: &DeviceTree) -> Result<Self, MemoryLayoutError> {
+        let root = tree.root();
+        let memory_node = root.child("memory").ok_or(MemoryLayoutError::MissingMemory)?;
+        let address_cells = root.address_cells().map_err(|_err| MemoryLayoutError::MissingMemory)?;
+        let size_cells = root.size_cells().map_err(|_err| MemoryLayoutError::MissingMemory)?;
+        let memory_ranges = parse_reg(memory_node, address_cells, size_cells)
+            .ok_or(MemoryLayoutError::MissingMemory)?;
+        check_no_overlaps(&memory_ranges).ok_or(MemoryLayoutError::Overlap)?;
+
+        let mut reservations = Vec::new();
+        if let Some(reserved_memory) = root.child("reserved-memory") {
+            let address_cells = reserved_memory
+                .address_cells()
+                .map_err(|_err| MemoryLayoutError::MalformedReservation)?;
+            let size_cells = reserved_memory
+                .size_cells()
+                .map_err(|_err| MemoryLayoutError::MalformedReservation)?;
+            for child in reserved_memory.children() {
+                let kind = if child.property("no-map").is_some() {
+                    MemoryKind::Device
+                } else {
+                    MemoryKind::Reserved
+                };
+                let child_ranges = parse_reg(child, address_cells, size_cells)
+                    .ok_or(MemoryLayoutError::MalformedReservation)?;
+                for (start, end) in child_ranges {
+                    reservations.push((start, end, kind));
+                }
+            }
+        }
+        check_no_overlaps(&reservations.iter().map(|&(start, end, _)| (start, end)).collect::<Vec<_>>())
+            .ok_or(MemoryLayoutError::Overlap)?;
+        for &(start, end, _) in &reservations {
+            if !memory_ranges.iter().any(|&(mem_start, mem_end)| start >= mem_start && end <= mem_end) {
+                return Err(MemoryLayoutError::ReservationOutsideRam);
+            }
+        }
+
+        let mut ranges: Vec<MemoryRange> = memory_ranges
+            .into_iter()
+            .map(|(start, end)| MemoryRange { start, end, kind: MemoryKind::Normal })
+            .collect();
+        ranges.extend(
+            reservations
+                .into_iter()
+                .map(|(start, end, kind)| MemoryRange { start, end, kind }),
+        );
+        ranges.sort_by_key(|range| range.start);
+
+        Ok(Self { ranges })
+    }
+
+    /// Returns every range making up this layout, sorted by start address.
+    #[must_use]
+    pub fn ranges(&self) -> &[MemoryRange] {
+        &self.ranges
+    }
+
+    /// Maps every range of this layout into `idmap`, using
+    /// [`MEMORY_ATTRIBUTES`] for [`MemoryKind::Normal`] and
+    /// [`MemoryKind::Reserved`] ranges and [`DEVICE_ATTRIBUTES`] for
+    /// [`MemoryKind::Device`] ranges.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `idmap` cannot map one of the ranges.
+    pub fn map_into(&self, idmap: &mut IdMap) -> Result<(), MapError> {
+        for range in &self.ranges {
+            let attributes = match range.kind {
+                MemoryKind::Normal | MemoryKind::Reserved => MEMORY_ATTRIBUTES,
+                MemoryKind::Device => DEVICE_ATTRIBUTES,
+            };
+            idmap.map_range(
+                &MemoryRegion::new(range.start as usize, range.end as usize),
+                attributes,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Finds the largest contiguous sub-range of [`MemoryKind::Normal`]
+    /// memory not carved out by a [`MemoryKind::Reserved`] or
+    /// [`MemoryKind::Device`] range, as a `(start, end)` pair.
+    ///
+    /// Returns `None` if every normal range is fully reserved.
+    #[must_use]
+    pub fn usable_heap_region(&self) -> Option<(u64, u64)> {
+        let mut best: Option<(u64, u64)> = None;
+        let mut consider = |candidate: (u64, u64)| {
+            if candidate.1 > candidate.0
+                && best.is_none_or(|best| candidate.1 - candidate.0 > best.1 - best.0)
+            {
+                best = Some(candidate);
+            }
+        };
+
+        for normal in self.ranges.iter().filter(|range| range.kind == MemoryKind::Normal) {
+            let mut carve_outs: Vec<&MemoryRange> = self
+                .ranges
+                .iter()
+                .filter(|range| {
+                    range.kind != MemoryKind::Normal && range.start >= normal.start && range.end <= normal.end
+                })
+                .collect();
+            carve_outs.sort_by_key(|range| range.start);
+
+            let mut cursor = normal.start;
+            for carve_out in carve_outs {
+                consider((cursor, carve_out.start));
+                cursor = cursor.max(carve_out.end);
+            }
+            consider((cursor, normal.end));
+        }
+
+        best
+    }
+}
+
+/// Reads `node`'s `reg` property as a list of `(start, end)` ranges, given
+/// the address and size cell widths that apply to it (its parent's
+/// `#address-cells`/`#size-cells`).
+///
+/// Returns `None` if `reg` is missing or its length isn't a whole number of
+/// `address_cells + size_cells`-sized entries.
+fn parse_reg(node: &DeviceTreeNode, address_cells: u32, size_cells: u32) -> Option<Vec<(u64, u64)>> {
+    let prop = node.property("reg")?;
+    let mut cells = prop.as_u32_array().ok()?.peekable();
+    let mut ranges = Vec::new();
+    while cells.peek().is_some() {
+        let start = read_cells(&mut cells, address_cells)?;
+        let size = read_cells(&mut cells, size_cells)?;
+        ranges.push((start, start.checked_add(size)?));
+    }
+    Some(ranges)
+}
+
+/// Combines the next `count` cells of `cells` into a single big-endian
+/// value, most significant cell first.
+fn read_cells(cells: &mut impl Iterator<Item = u32>, count: u32) -> Option<u64> {
+    let mut value = 0u64;
+    for _ in 0..count {
+        value = (value << 32) | u64::from(cells.next()?);
+    }
+    Some(value)
+}
+
+/// Returns `None` if any two of `ranges` overlap, `Some(())` otherwise.
+fn check_no_overlaps(ranges: &[(u64, u64)]) -> Option<()> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_by_key(|&(start, _)| start);
+    if sorted.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+        None
+    } else {
+        Some(())
+    }
+}
@@ -19,6 +19,12 @@ pub type ConsoleImpl = <PlatformImpl as Platform>::Console;
 pub trait Platform {
     type Console: Read + ReadReady + Send + Write + WriteReady;
 
+    /// The maximum number of CPU cores this platform may boot.
+    ///
+    /// This bounds the fixed-capacity per-core state the hypervisor keeps,
+    /// e.g. suspend contexts and secondary stacks.
+    const MAX_CORES: usize;
+
     /// Creates an instance of the platform.
     ///
     /// # Safety
@@ -32,6 +38,19 @@ pub trait Platform {
     /// This should return `Some` the first time it is called, but may return `None` on subsequent
     /// calls.
     fn parts(&mut self) -> Option<PlatformParts<Self::Console>>;
+
+    /// Called when the guest requests a system-wide power event via PSCI.
+    ///
+    /// Returning `true` forwards the request to the real secure monitor;
+    /// returning `false` vetoes it (the guest's PSCI call then fails with
+    /// `DENIED`), giving the platform a chance to log, snapshot, or
+    /// otherwise intervene before the board actually powers off or resets.
+    ///
+    /// The default implementation always allows the request.
+    fn on_system_event(event: SystemEvent) -> bool {
+        let _ = event;
+        true
+    }
 }
 
 /// The drivers provided by each platform.
@@ -39,3 +58,15 @@ pub struct PlatformParts<Console> {
     /// The primary console.
     pub console: Console,
 }
+
+/// A guest-initiated PSCI system-wide power event, passed to
+/// [`Platform::on_system_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemEvent {
+    /// The guest requested `SYSTEM_OFF`.
+    Off,
+    /// The guest requested `SYSTEM_RESET`.
+    Reset,
+    /// The guest requested `SYSTEM_RESET2`.
+    Reset2,
+}
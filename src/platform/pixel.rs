@@ -39,6 +39,8 @@ impl Pixel {
 impl Platform for Pixel {
     type Console = SynopsysUart<'static>;
 
+    const MAX_CORES: usize = 8;
+
     unsafe fn create() -> Self {
         let uart = SynopsysUart::new(
             // SAFETY: UART_BASE_ADDRESS is valid and mapped, and `create` is only called once so
@@ -37,6 +37,8 @@ impl Qemu {
 impl Platform for Qemu {
     type Console = Uart<'static>;
 
+    const MAX_CORES: usize = 8;
+
     unsafe fn create() -> Self {
         let mut uart = Uart::new(
             // SAFETY: UART_BASE_ADDRESS is valid and mapped, and `create` is only called once so
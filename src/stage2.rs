@@ -0,0 +1,291 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! EL2 stage-2 translation tables.
+//!
+//! These confine an EL1 guest to the physical memory and devices the
+//! hypervisor explicitly grants it: any intermediate physical address (IPA)
+//! that isn't covered by a mapped region faults as a stage-2 abort rather
+//! than reaching hypervisor or unrelated device memory.
+//!
+//! The tables use a 4 KiB granule and support the usual 3 translation
+//! levels, so a region can be mapped as a 1 GiB block, a 2 MiB block, or
+//! individual 4 KiB pages, depending on the alignment and size of the
+//! region. Regions must be page-aligned; finer-grained splitting (e.g. a
+//! 1 GiB region with a 4 KiB hole) is not supported.
+//!
+//! Building a [`Stage2PageTable`] and installing it with
+//! [`crate::hypervisor::set_stage2_page_table`] is not yet wired into the
+//! boot path in `main.rs`, which currently stops before ever loading and
+//! entering a guest: no caller derives regions from the device tree's
+//! `/memory` nodes and installs them, so guests are not yet confined. That
+//! wiring is follow-up work once `main.rs` loads a guest image and jumps to
+//! it.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::arch::{self, vtcr_el2, vttbr_el2};
+
+const PAGE_SIZE: u64 = 4096;
+const L1_BLOCK_SIZE: u64 = 1 << 30;
+const L2_BLOCK_SIZE: u64 = 1 << 21;
+const ENTRIES_PER_TABLE: usize = 512;
+
+/// The memory type to use for a stage-2 mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage2MemoryType {
+    /// Normal, cacheable memory such as guest RAM.
+    Normal,
+    /// Device memory, mapped non-cacheable and non-reorderable.
+    Device,
+}
+
+/// A region to map in a [`Stage2PageTable`].
+#[derive(Debug, Clone, Copy)]
+pub struct Stage2Region {
+    /// The intermediate physical address the guest sees.
+    pub ipa: u64,
+    /// The physical address this region is backed by.
+    pub pa: u64,
+    /// The size of the region in bytes.
+    pub size: u64,
+    /// The memory type to map this region as.
+    pub memory_type: Stage2MemoryType,
+}
+
+/// An error building a [`Stage2PageTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage2Error {
+    /// A region's `ipa`, `pa`, or `size` is not a multiple of the page size.
+    Misaligned,
+    /// A region's `ipa` falls outside the range addressable by the
+    /// translation tables.
+    IpaOutOfRange,
+}
+
+/// A stage-2 translation table hierarchy.
+///
+/// Any IPA not covered by a region passed to [`Stage2PageTable::build`] is
+/// left unmapped, so a guest access to it takes a stage-2 abort, which
+/// `handle_sync_lower` reports (no trapped access is emulated or forwarded).
+pub struct Stage2PageTable {
+    root: Box<PageTable>,
+    // Kept alive for as long as the root table may be walked by hardware;
+    // never read from Rust once `activate` has been called.
+    _tables: Vec<Box<PageTable>>,
+}
+
+impl Stage2PageTable {
+    /// Builds a stage-2 page table hierarchy mapping the given regions.
+    ///
+    /// Any IPA outside of `regions` is left unmapped, so a guest access to it
+    /// traps to EL2 as a stage-2 abort.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a region isn't page-aligned, or if it extends
+    /// beyond the 39-bit IPA space this 3-level, 4 KiB-granule hierarchy can
+    /// address.
+    pub fn build(regions: &[Stage2Region]) -> Result<Self, Stage2Error> {
+        let mut root = PageTable::empty();
+        let mut tables = Vec::new();
+
+        for region in regions {
+            if region.ipa % PAGE_SIZE != 0 || region.pa % PAGE_SIZE != 0 || region.size % PAGE_SIZE != 0
+            {
+                return Err(Stage2Error::Misaligned);
+            }
+            if region.ipa.checked_add(region.size).is_none_or(|end| end > 1 << 39) {
+                return Err(Stage2Error::IpaOutOfRange);
+            }
+
+            let mut offset = 0;
+            while offset < region.size {
+                let ipa = region.ipa + offset;
+                let pa = region.pa + offset;
+                let remaining = region.size - offset;
+                let block_size = if ipa % L1_BLOCK_SIZE == 0 && pa % L1_BLOCK_SIZE == 0 && remaining >= L1_BLOCK_SIZE
+                {
+                    L1_BLOCK_SIZE
+                } else if ipa % L2_BLOCK_SIZE == 0 && pa % L2_BLOCK_SIZE == 0 && remaining >= L2_BLOCK_SIZE {
+                    L2_BLOCK_SIZE
+                } else {
+                    PAGE_SIZE
+                };
+
+                map_one(&mut root, &mut tables, ipa, pa, block_size, region.memory_type);
+                offset += block_size;
+            }
+        }
+
+        Ok(Self {
+            root,
+            _tables: tables,
+        })
+    }
+
+    /// Programs `VTTBR_EL2` and `VTCR_EL2` with this table and enables
+    /// stage-2 translation in `HCR_EL2`.
+    ///
+    /// # Safety
+    ///
+    /// This must only be called while running at EL2, before `eret`-ing to
+    /// the EL1 guest this table confines. The caller must ensure `self`
+    /// outlives the guest's use of stage-2 translation (it should never be
+    /// dropped while the guest may be running).
+    pub unsafe fn activate(&self) {
+        let root_pa = core::ptr::from_ref(&*self.root) as u64;
+
+        // SAFETY: `root_pa` points at a valid, live stage-2 table hierarchy,
+        // and the caller guarantees we are running at EL2 before dropping to
+        // the guest.
+        unsafe {
+            vttbr_el2::write(root_pa);
+            vtcr_el2::write(vtcr_value());
+
+            let mut hcr = arch::hcr_el2::read();
+            hcr |= arch::hcr_el2::VM;
+            arch::hcr_el2::write(hcr);
+        }
+        arch::isb();
+    }
+}
+
+/// Computes the `VTCR_EL2` value for a 3-level, 4 KiB-granule hierarchy
+/// covering a 39-bit IPA space, with a 40-bit physical address size.
+fn vtcr_value() -> u64 {
+    const T0SZ: u64 = 64 - 39;
+    const SL0_LEVEL1_START: u64 = 0b01 << 6;
+    const IRGN0_WRITE_BACK: u64 = 0b01 << 8;
+    const ORGN0_WRITE_BACK: u64 = 0b01 << 10;
+    const SH0_INNER_SHAREABLE: u64 = 0b11 << 12;
+    const TG0_4KB: u64 = 0b00 << 14;
+    const PS_40_BIT: u64 = 0b010 << 16;
+
+    T0SZ | SL0_LEVEL1_START | IRGN0_WRITE_BACK | ORGN0_WRITE_BACK | SH0_INNER_SHAREABLE | TG0_4KB | PS_40_BIT
+}
+
+/// Finds (allocating if necessary) the next-level table an entry points to.
+fn next_table<'a>(entry: &mut Descriptor, tables: &'a mut Vec<Box<PageTable>>) -> &'a mut PageTable {
+    if !entry.is_table() {
+        let mut table = PageTable::empty();
+        let ptr: *mut PageTable = &mut *table;
+        *entry = Descriptor::table(ptr);
+        tables.push(table);
+    }
+    let ptr = entry.table_address() as *mut PageTable;
+    // SAFETY: `ptr` was derived from a `Descriptor::table` created above (on
+    // this call or a previous one), pointing at a table kept alive in
+    // `tables` for the lifetime of the enclosing `Stage2PageTable`.
+    unsafe { &mut *ptr }
+}
+
+fn map_one(
+    root: &mut PageTable,
+    tables: &mut Vec<Box<PageTable>>,
+    ipa: u64,
+    pa: u64,
+    block_size: u64,
+    memory_type: Stage2MemoryType,
+) {
+    let l1_index = index(ipa, 30);
+    if block_size == L1_BLOCK_SIZE {
+        root.entries[l1_index] = Descriptor::block(pa, memory_type);
+        return;
+    }
+
+    let l2_table = next_table(&mut root.entries[l1_index], tables);
+    let l2_index = index(ipa, 21);
+    if block_size == L2_BLOCK_SIZE {
+        l2_table.entries[l2_index] = Descriptor::block(pa, memory_type);
+        return;
+    }
+
+    let l3_table = next_table(&mut l2_table.entries[l2_index], tables);
+    let l3_index = index(ipa, 12);
+    l3_table.entries[l3_index] = Descriptor::page(pa, memory_type);
+}
+
+fn index(ipa: u64, shift: u32) -> usize {
+    ((ipa >> shift) & 0x1ff) as usize
+}
+
+#[repr(C, align(4096))]
+struct PageTable {
+    entries: [Descriptor; ENTRIES_PER_TABLE],
+}
+
+impl PageTable {
+    fn empty() -> Box<Self> {
+        Box::new(Self {
+            entries: [Descriptor::invalid(); ENTRIES_PER_TABLE],
+        })
+    }
+}
+
+/// A single stage-2 translation table descriptor.
+///
+/// Layout follows the Armv8-A VMSA stage-2 block/page descriptor format:
+/// bit 0 is Valid, bit 1 distinguishes Table (1) from Block (0) at levels 1
+/// and 2 (it is always 1, for Page, at level 3), bits `[5:2]` hold
+/// `MemAttr`, bits `[7:6]` hold `S2AP`, bits `[9:8]` hold `SH`, and bit 10 is
+/// the Access Flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Descriptor(u64);
+
+impl Descriptor {
+    const VALID: u64 = 1 << 0;
+    const TABLE_OR_PAGE: u64 = 1 << 1;
+    const S2AP_READ_WRITE: u64 = 0b11 << 6;
+    const SH_INNER_SHAREABLE: u64 = 0b11 << 8;
+    const AF: u64 = 1 << 10;
+
+    /// `MemAttr` encoding for Device-nGnRnE memory.
+    const MEM_ATTR_DEVICE: u64 = 0b0000 << 2;
+    /// `MemAttr` encoding for Normal, Inner/Outer Write-Back Cacheable memory.
+    const MEM_ATTR_NORMAL: u64 = 0b1111 << 2;
+
+    const fn invalid() -> Self {
+        Self(0)
+    }
+
+    fn mem_attr(memory_type: Stage2MemoryType) -> u64 {
+        match memory_type {
+            Stage2MemoryType::Normal => Self::MEM_ATTR_NORMAL,
+            Stage2MemoryType::Device => Self::MEM_ATTR_DEVICE,
+        }
+    }
+
+    fn block(output_address: u64, memory_type: Stage2MemoryType) -> Self {
+        Self(
+            Self::VALID
+                | Self::AF
+                | Self::SH_INNER_SHAREABLE
+                | Self::S2AP_READ_WRITE
+                | Self::mem_attr(memory_type)
+                | (output_address & !(PAGE_SIZE - 1)),
+        )
+    }
+
+    fn page(output_address: u64, memory_type: Stage2MemoryType) -> Self {
+        Self(Self::TABLE_OR_PAGE | Self::block(output_address, memory_type).0)
+    }
+
+    fn table(next_level: *mut PageTable) -> Self {
+        Self(Self::VALID | Self::TABLE_OR_PAGE | (next_level as u64))
+    }
+
+    fn is_table(self) -> bool {
+        self.0 & (Self::VALID | Self::TABLE_OR_PAGE) == Self::VALID | Self::TABLE_OR_PAGE
+    }
+
+    fn table_address(self) -> u64 {
+        self.0 & !0xfff
+    }
+}
@@ -6,6 +6,19 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+//! A fault-injection harness asserting the hypervisor's memory-isolation
+//! policy.
+//!
+//! Each [`FaultCase`] in [`cases`] probes one boundary the isolation policy
+//! is supposed to enforce (the protected region, device memory, a read-only
+//! normal page) with the access that should be rejected, and records the
+//! `ESR_EL1` exception class the resulting fault should report. `main` runs
+//! the cases in order; `Exceptions::sync_current` checks the fault it
+//! caught against the case currently running, reports the result over the
+//! PL011 UART, and resumes execution at a point recorded immediately before
+//! the faulting instruction so the next case can run. Only once every case
+//! has been exercised does the test power off.
+
 #![no_std]
 #![no_main]
 
@@ -16,12 +29,19 @@ use core::arch::asm;
 use core::fmt::Write;
 use core::panic::PanicInfo;
 use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use spin::Once;
 use spin::mutex::{SpinMutex, SpinMutexGuard};
 
 const UART_BASE: usize = 0x0900_0000;
 const RITM_BASE: usize = 0x4000_0000;
 
+/// `ESR_EL1.EC` for a Data Abort taken without a change in exception level.
+const EC_DATA_ABORT_CURRENT_EL: u64 = 0x25;
+/// `ESR_EL1.EC` for an Instruction Abort taken without a change in exception
+/// level.
+const EC_INSTRUCTION_ABORT_CURRENT_EL: u64 = 0x21;
+
 exception_handlers!(Exceptions);
 entry!(main);
 
@@ -36,23 +56,188 @@ fn get_uart() -> SpinMutexGuard<'static, Uart<'static>> {
     .lock()
 }
 
+/// The kind of access a [`FaultCase`] attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+    Exec,
+}
+
+/// One boundary the memory-isolation policy should reject an access to.
+struct FaultCase {
+    /// Human-readable description, printed alongside the result.
+    label: &'static str,
+    /// The address to probe.
+    address: usize,
+    /// The kind of access to attempt against `address`.
+    access: Access,
+    /// The `ESR_EL1.EC` value the resulting exception should report.
+    expected_ec: u64,
+}
+
+/// The cases the harness runs, in order.
+///
+/// `power_off`'s own address stands in for a read-only normal page: its code
+/// lives in ordinary RAM but is mapped read-only and executable, so a write
+/// to it should fault the same way a guest patching the hypervisor's text
+/// would.
+fn cases() -> [FaultCase; 4] {
+    [
+        FaultCase {
+            label: "read protected memory",
+            address: RITM_BASE,
+            access: Access::Read,
+            expected_ec: EC_DATA_ABORT_CURRENT_EL,
+        },
+        FaultCase {
+            label: "write protected memory",
+            address: RITM_BASE,
+            access: Access::Write,
+            expected_ec: EC_DATA_ABORT_CURRENT_EL,
+        },
+        FaultCase {
+            label: "execute device memory",
+            address: UART_BASE,
+            access: Access::Exec,
+            expected_ec: EC_INSTRUCTION_ABORT_CURRENT_EL,
+        },
+        FaultCase {
+            label: "write read-only normal memory",
+            address: power_off as usize,
+            access: Access::Write,
+            expected_ec: EC_DATA_ABORT_CURRENT_EL,
+        },
+    ]
+}
+
+/// The case currently running: `(label, expected_ec)`, read by
+/// `Exceptions::sync_current` to judge the fault it caught.
+static CURRENT_CASE: SpinMutex<Option<(&'static str, u64)>> = SpinMutex::new(None);
+/// How many cases have failed so far.
+static FAILURES: AtomicUsize = AtomicUsize::new(0);
+/// Set just before the faulting instruction of each probe, so the handler
+/// can resume execution right after it regardless of which probe faulted.
+static mut RESUME_PC: usize = 0;
+
 fn main(_arg0: u64, _arg1: u64, _arg2: u64, _arg3: u64) -> ! {
     writeln!(get_uart(), "TEST: Starting isolation test").unwrap();
 
-    writeln!(
-        get_uart(),
-        "TEST: Attempting to read protected memory at {:#x}",
-        RITM_BASE,
-    )
-    .unwrap();
+    for case in cases() {
+        writeln!(get_uart(), "TEST: probing: {}", case.label).unwrap();
+        *CURRENT_CASE.lock() = Some((case.label, case.expected_ec));
+
+        // SAFETY: Every case here is expected to fault before the access
+        // completes, so the probe never actually reads or writes through an
+        // address it shouldn't.
+        unsafe {
+            match case.access {
+                Access::Read => probe_read(case.address),
+                Access::Write => probe_write(case.address),
+                Access::Exec => probe_exec(case.address),
+            }
+        }
 
-    // We expect this to trap
-    let val = unsafe { core::ptr::read_volatile(RITM_BASE as *const u64) };
+        // Reaching this point means the access did not fault at all.
+        writeln!(get_uart(), "TEST: FAILED: {}: access did not fault", case.label).unwrap();
+        FAILURES.fetch_add(1, Ordering::Relaxed);
+    }
 
-    writeln!(get_uart(), "TEST: FAILED: Read successful: {:#x}", val).unwrap();
+    if FAILURES.load(Ordering::Relaxed) == 0 {
+        writeln!(get_uart(), "TEST: PASSED: all isolation cases faulted as expected").unwrap();
+    } else {
+        writeln!(
+            get_uart(),
+            "TEST: FAILED: {} case(s) did not fault as expected",
+            FAILURES.load(Ordering::Relaxed),
+        )
+        .unwrap();
+    }
     power_off();
 }
 
+/// Attempts to read `addr`, recording a resume point immediately before the
+/// instruction that does so.
+///
+/// # Safety
+///
+/// The caller must be prepared for `addr` to actually be readable, in which
+/// case this reads through it.
+#[inline(never)]
+unsafe fn probe_read(addr: usize) {
+    // SAFETY: This records the address of the label following the `ldr`
+    // into `RESUME_PC` before the `ldr` executes, so if the `ldr` faults,
+    // `RESUME_PC` already holds a valid resume address. The load target
+    // itself is only read, never written.
+    unsafe {
+        asm!(
+            "adr {resume}, 2f",
+            "str {resume}, [{resume_ptr}]",
+            "ldr {tmp}, [{addr}]",
+            "2:",
+            resume = out(reg) _,
+            resume_ptr = in(reg) &raw mut RESUME_PC,
+            tmp = out(reg) _,
+            addr = in(reg) addr,
+            options(nostack),
+        );
+    }
+}
+
+/// Attempts to write `addr`, recording a resume point immediately before the
+/// instruction that does so.
+///
+/// # Safety
+///
+/// The caller must be prepared for `addr` to actually be writable, in which
+/// case this clobbers the word at `addr`.
+#[inline(never)]
+unsafe fn probe_write(addr: usize) {
+    // SAFETY: Same resume-point protocol as `probe_read`, but guarding a
+    // `str` instead of a `ldr`.
+    unsafe {
+        asm!(
+            "adr {resume}, 2f",
+            "str {resume}, [{resume_ptr}]",
+            "str {val}, [{addr}]",
+            "2:",
+            resume = out(reg) _,
+            resume_ptr = in(reg) &raw mut RESUME_PC,
+            val = in(reg) 0u64,
+            addr = in(reg) addr,
+            options(nostack),
+        );
+    }
+}
+
+/// Attempts to branch to and execute at `addr`, recording a resume point
+/// immediately after the branch.
+///
+/// # Safety
+///
+/// The caller must be prepared for `addr` to actually be executable, in
+/// which case this jumps to and executes whatever is there.
+#[inline(never)]
+unsafe fn probe_exec(addr: usize) {
+    // SAFETY: Same resume-point protocol as `probe_read`/`probe_write`. An
+    // Instruction Abort reports `ELR_EL1` as the faulting fetch address
+    // itself (`addr`), not the `blr`, so `sync_current` must overwrite
+    // `ELR_EL1` with `RESUME_PC` rather than merely advancing it.
+    unsafe {
+        asm!(
+            "adr {resume}, 2f",
+            "str {resume}, [{resume_ptr}]",
+            "blr {addr}",
+            "2:",
+            resume = out(reg) _,
+            resume_ptr = in(reg) &raw mut RESUME_PC,
+            addr = in(reg) addr,
+            out("lr") _,
+            options(nostack),
+        );
+    }
+}
+
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
     writeln!(get_uart(), "TEST: PANIC: {}", info).unwrap();
@@ -63,18 +248,30 @@ struct Exceptions;
 impl ExceptionHandlers for Exceptions {
     extern "C" fn sync_current(_register_state: RegisterStateRef) {
         let esr = read_esr_el1();
-
-        // Check for Data Abort (EC = 0x25 or 0x24 if injected verbatim)
         let ec = esr.ec();
-        if ec == 0x25 || ec == 0x24 {
+
+        let Some((label, expected_ec)) = *CURRENT_CASE.lock() else {
+            panic!("unexpected exception before any case started: ESR={esr:#x}");
+        };
+
+        if ec == expected_ec {
+            writeln!(get_uart(), "TEST: PASSED: {label}").unwrap();
+        } else {
             writeln!(
                 get_uart(),
-                "TEST: Caught expected Data Abort! Isolation test passed.",
+                "TEST: FAILED: {label}: expected EC={expected_ec:#x}, got EC={ec:#x} (ESR={esr:#x})",
             )
             .unwrap();
-            power_off();
-        } else {
-            panic!("Unexpected exception: ESR={:#x}", esr);
+            FAILURES.fetch_add(1, Ordering::Relaxed);
+        }
+
+        // Resume just past the faulting instruction so `main` moves on to
+        // the next case instead of re-faulting on this one forever.
+        // SAFETY: `RESUME_PC` was set by the probe currently running,
+        // immediately before the instruction that took this exception, so
+        // it is a valid address to resume execution at.
+        unsafe {
+            asm!("msr elr_el1, {resume_pc}", resume_pc = in(reg) RESUME_PC);
         }
     }
 }